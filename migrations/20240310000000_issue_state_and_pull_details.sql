@@ -0,0 +1,10 @@
+ALTER TABLE issues
+    ADD COLUMN issue_state VARCHAR,
+    ADD COLUMN issue_labels TEXT[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE pull_requests
+    ADD COLUMN merged_at TIMESTAMPTZ,
+    ADD COLUMN labels TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN review_decision VARCHAR,
+    ADD COLUMN additions BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN deletions BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,66 @@
+-- Rows left behind by the old insert helpers would block the new constraints. Rather than
+-- dropping them, give them placeholder parents (filled in by the next crawl) and report them.
+DO $$
+DECLARE
+    orphan_comments BIGINT;
+    orphan_issues BIGINT;
+BEGIN
+    SELECT COUNT(DISTINCT issue_id) INTO orphan_comments
+    FROM comments c
+    WHERE NOT EXISTS (SELECT 1 FROM issues i WHERE i.issue_id = c.issue_id);
+
+    IF orphan_comments > 0 THEN
+        RAISE WARNING 'adding % placeholder issues for comments without an issue', orphan_comments;
+    END IF;
+
+    INSERT INTO issues (issue_id, project_id, issue_title, issue_description)
+    SELECT DISTINCT c.issue_id, regexp_replace(c.issue_id, '/issues/[0-9]+/?$', ''), '', ''
+    FROM comments c
+    WHERE NOT EXISTS (SELECT 1 FROM issues i WHERE i.issue_id = c.issue_id);
+
+    SELECT COUNT(DISTINCT project_id) INTO orphan_issues
+    FROM issues i
+    WHERE NOT EXISTS (SELECT 1 FROM projects p WHERE p.project_id = i.project_id);
+
+    IF orphan_issues > 0 THEN
+        RAISE WARNING 'adding % placeholder projects for issues without a project', orphan_issues;
+    END IF;
+
+    INSERT INTO projects (project_id, project_logo)
+    SELECT DISTINCT i.project_id, ''
+    FROM issues i
+    WHERE NOT EXISTS (SELECT 1 FROM projects p WHERE p.project_id = i.project_id);
+END $$;
+
+ALTER TABLE issues
+    ADD CONSTRAINT issues_project_id_fkey
+    FOREIGN KEY (project_id) REFERENCES projects (project_id)
+    ON UPDATE CASCADE ON DELETE CASCADE;
+
+ALTER TABLE comments
+    ADD CONSTRAINT comments_issue_id_fkey
+    FOREIGN KEY (issue_id) REFERENCES issues (issue_id)
+    ON UPDATE CASCADE ON DELETE CASCADE;
+
+CREATE INDEX issues_project_id_idx ON issues (project_id);
+CREATE INDEX issues_issue_assignee_idx ON issues (issue_assignee);
+CREATE INDEX comments_issue_id_idx ON comments (issue_id);
+CREATE INDEX comments_creator_idx ON comments (creator);
+CREATE INDEX pull_requests_author_idx ON pull_requests (author);
+CREATE INDEX pull_requests_repository_idx ON pull_requests (repository);
+
+ALTER TABLE projects
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE issues
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE comments
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE pull_requests
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
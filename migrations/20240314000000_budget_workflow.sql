@@ -0,0 +1,17 @@
+-- issue_budget_approved: NULL while a budget awaits review, TRUE once approved, FALSE if rejected.
+ALTER TABLE issues
+    ADD COLUMN issue_budget_reviewed_by VARCHAR,
+    ADD COLUMN issue_budget_reviewed_at TIMESTAMPTZ;
+
+CREATE TABLE issue_budget_events (
+    event_id BIGSERIAL PRIMARY KEY,
+    issue_id VARCHAR NOT NULL REFERENCES issues (issue_id) ON UPDATE CASCADE ON DELETE CASCADE,
+    action VARCHAR NOT NULL,
+    old_budget INT,
+    new_budget INT,
+    actor VARCHAR NOT NULL,
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX issue_budget_events_issue_id_idx ON issue_budget_events (issue_id);
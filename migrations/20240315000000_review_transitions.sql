@@ -0,0 +1,12 @@
+CREATE TABLE review_transitions (
+    transition_id BIGSERIAL PRIMARY KEY,
+    issue_id VARCHAR NOT NULL REFERENCES issues (issue_id) ON UPDATE CASCADE ON DELETE CASCADE,
+    from_status review_status,
+    to_status review_status NOT NULL,
+    actor VARCHAR NOT NULL,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX review_transitions_issue_id_idx ON review_transitions (issue_id);
+CREATE INDEX issues_review_status_idx ON issues (review_status);
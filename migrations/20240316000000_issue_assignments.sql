@@ -0,0 +1,16 @@
+ALTER TABLE issues
+    ADD COLUMN issue_assigned_at TIMESTAMPTZ;
+
+CREATE TABLE issue_assignments (
+    assignment_id BIGSERIAL PRIMARY KEY,
+    issue_id VARCHAR NOT NULL REFERENCES issues (issue_id) ON UPDATE CASCADE ON DELETE CASCADE,
+    assignee VARCHAR NOT NULL,
+    action VARCHAR NOT NULL,
+    actor VARCHAR NOT NULL,
+    event_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (issue_id, assignee, action, event_at)
+);
+
+CREATE INDEX issue_assignments_issue_id_idx ON issue_assignments (issue_id);
+CREATE INDEX issue_assignments_assignee_idx ON issue_assignments (assignee);
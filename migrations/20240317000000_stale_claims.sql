@@ -0,0 +1,2 @@
+ALTER TABLE issues
+    ADD COLUMN stale_flagged_at TIMESTAMPTZ;
@@ -0,0 +1,10 @@
+CREATE TABLE issue_status_transitions (
+    transition_id BIGSERIAL PRIMARY KEY,
+    issue_id VARCHAR NOT NULL REFERENCES issues (issue_id) ON UPDATE CASCADE ON DELETE CASCADE,
+    from_status VARCHAR,
+    to_status VARCHAR NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX issue_status_transitions_issue_id_idx ON issue_status_transitions (issue_id);
+CREATE INDEX issues_issue_status_idx ON issues (issue_status);
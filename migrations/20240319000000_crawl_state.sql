@@ -0,0 +1,8 @@
+CREATE TABLE crawl_state (
+    query_key VARCHAR PRIMARY KEY,
+    last_window_start TIMESTAMPTZ NOT NULL,
+    last_window_end TIMESTAMPTZ NOT NULL,
+    updated_high_water TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
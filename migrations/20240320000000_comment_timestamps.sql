@@ -0,0 +1,5 @@
+-- `time` keeps the GitHub creation time (UTC); edits are tracked separately from the row's `updated_at`.
+ALTER TABLE comments
+    ADD COLUMN comment_updated_at TIMESTAMPTZ;
+
+CREATE INDEX comments_issue_id_time_idx ON comments (issue_id, time);
@@ -0,0 +1,12 @@
+ALTER TABLE projects
+    ADD COLUMN tracked_labels TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN bounty_label VARCHAR,
+    ADD COLUMN maintainers TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN slack_channel VARCHAR,
+    ADD COLUMN budget_cap INT CHECK (budget_cap >= 0),
+    ADD COLUMN active_from TIMESTAMPTZ,
+    ADD COLUMN active_until TIMESTAMPTZ,
+    ADD CONSTRAINT projects_active_range_check
+        CHECK (active_from IS NULL OR active_until IS NULL OR active_from < active_until);
+
+CREATE INDEX projects_active_idx ON projects (active_from, active_until);
@@ -0,0 +1,13 @@
+ALTER TABLE projects
+    ADD COLUMN description TEXT,
+    ADD COLUMN primary_language VARCHAR,
+    ADD COLUMN topics TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN stars INT,
+    ADD COLUMN forks INT,
+    ADD COLUMN license VARCHAR,
+    ADD COLUMN default_branch VARCHAR,
+    ADD COLUMN open_issues INT,
+    ADD COLUMN has_contributing BOOLEAN,
+    ADD COLUMN enriched_at TIMESTAMPTZ;
+
+CREATE INDEX projects_enriched_at_idx ON projects (enriched_at);
@@ -0,0 +1,46 @@
+ALTER TABLE issues
+    ADD COLUMN issue_author VARCHAR,
+    ADD COLUMN issue_opened_at TIMESTAMPTZ;
+
+CREATE INDEX issues_issue_author_idx ON issues (issue_author);
+
+ALTER TABLE projects
+    ADD COLUMN leaderboard_posted_at TIMESTAMPTZ;
+
+-- Aggregated from `issues` and `pull_requests` by `refresh_contributors`, never written directly.
+CREATE TABLE contributors (
+    login VARCHAR PRIMARY KEY,
+    issues_opened INT NOT NULL DEFAULT 0,
+    prs_merged INT NOT NULL DEFAULT 0,
+    bounty_earned BIGINT NOT NULL DEFAULT 0,
+    projects_touched INT NOT NULL DEFAULT 0,
+    first_activity_at TIMESTAMPTZ,
+    last_activity_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- One row per credited action: an issue opened, or a pull request merged together with the
+-- approved budget of the issues it closed.
+CREATE VIEW contributor_activity AS
+SELECT i.issue_author AS login,
+       i.project_id,
+       i.issue_opened_at AS activity_at,
+       1 AS issues_opened,
+       0 AS prs_merged,
+       0::BIGINT AS bounty
+FROM issues i
+WHERE i.issue_author IS NOT NULL AND i.issue_author <> '' AND i.issue_opened_at IS NOT NULL
+UNION ALL
+SELECT p.author AS login,
+       p.repository AS project_id,
+       p.merged_at AS activity_at,
+       0 AS issues_opened,
+       1 AS prs_merged,
+       COALESCE((
+           SELECT SUM(l.issue_budget)
+           FROM issues l
+           WHERE l.issue_linked_pr = p.pull_id AND l.issue_budget_approved
+       ), 0)::BIGINT AS bounty
+FROM pull_requests p
+WHERE p.merged_at IS NOT NULL AND p.author <> '';
@@ -0,0 +1,3 @@
+-- The Slack workspace `slack_channel` belongs to, as connected on flows.network.
+ALTER TABLE projects
+    ADD COLUMN slack_workspace VARCHAR;
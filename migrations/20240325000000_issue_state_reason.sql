@@ -0,0 +1,4 @@
+-- Kept so the lifecycle can be recomputed from the stored row when a pull request links to
+-- an issue after the issue itself was crawled.
+ALTER TABLE issues
+    ADD COLUMN issue_state_reason VARCHAR;
@@ -0,0 +1,4 @@
+-- Everyone assigned to the issue. `issue_assignee` keeps the one claimant the lifecycle and
+-- the stale-claim timer follow.
+ALTER TABLE issues
+    ADD COLUMN issue_assignees VARCHAR[] NOT NULL DEFAULT '{}';
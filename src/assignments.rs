@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::issues_tracker::{AssignmentAction, OuterIssue};
+
+impl AssignmentAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AssignmentAction::Assigned => "assigned",
+            AssignmentAction::Unassigned => "unassigned",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Assignment {
+    pub assignment_id: i64,
+    pub issue_id: String,
+    pub assignee: String,
+    pub action: String,
+    pub actor: String,
+    pub event_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Stores the assignment timeline GitHub returned for `issue` and its current assignees. The
+// lifecycle and the stale-claim timer follow a single claimant, `issue_assignee`, taken as the
+// first assignee GitHub lists; `issue_assigned_at` is when that claimant was last assigned,
+// so it survives re-crawls.
+pub async fn record_assignments(pool: &PgPool, issue: &OuterIssue) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for event in &issue.assignment_events {
+        let event_at = DateTime::parse_from_rfc3339(&event.created_at)?.with_timezone(&Utc);
+        sqlx::query!(
+            r#"
+            INSERT INTO issue_assignments (issue_id, assignee, action, actor, event_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (issue_id, assignee, action, event_at) DO NOTHING
+            "#,
+            issue.url,
+            event.assignee,
+            event.action.as_str(),
+            event.actor,
+            event_at
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let current = issue.assignees.first();
+    let assigned_at = match current {
+        Some(assignee) => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT MAX(event_at)
+                FROM issue_assignments
+                WHERE issue_id = $1 AND assignee = $2 AND action = 'assigned'
+                "#,
+                issue.url,
+                assignee
+            )
+            .fetch_one(&mut *tx)
+            .await?
+        }
+        None => None,
+    };
+
+    // Without a recorded event (older than the fetched timeline) keep the stored time for
+    // the same assignee, or start the clock now for a new one.
+    sqlx::query!(
+        r#"
+        UPDATE issues
+        SET issue_assignee = $2,
+            issue_assignees = $4,
+            issue_assigned_at = CASE
+                WHEN $2::VARCHAR IS NULL THEN NULL
+                ELSE COALESCE(
+                    $3,
+                    CASE WHEN issue_assignee IS NOT DISTINCT FROM $2 THEN issue_assigned_at END,
+                    now())
+            END,
+            updated_at = now()
+        WHERE issue_id = $1
+          AND (issue_assignee IS DISTINCT FROM $2
+               OR issue_assignees IS DISTINCT FROM $4
+               OR ($3::TIMESTAMPTZ IS NOT NULL AND issue_assigned_at IS DISTINCT FROM $3))
+        "#,
+        issue.url,
+        current,
+        assigned_at,
+        &issue.assignees
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn assignment_history(pool: &PgPool, issue_id: &str) -> anyhow::Result<Vec<Assignment>> {
+    let history = sqlx::query_as::<_, Assignment>(
+        r#"
+        SELECT *
+        FROM issue_assignments
+        WHERE issue_id = $1
+        ORDER BY event_at, assignment_id
+        "#,
+    )
+    .bind(issue_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(history)
+}
@@ -0,0 +1,348 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use sqlx::{Postgres, Transaction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetAction {
+    Propose,
+    Approve,
+    Reject,
+    Change,
+}
+
+impl BudgetAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetAction::Propose => "propose",
+            BudgetAction::Approve => "approve",
+            BudgetAction::Reject => "reject",
+            BudgetAction::Change => "change",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct BudgetEvent {
+    pub event_id: i64,
+    pub issue_id: String,
+    pub action: String,
+    pub old_budget: Option<i32>,
+    pub new_budget: Option<i32>,
+    pub actor: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct ProjectBudgetSummary {
+    pub project_id: String,
+    pub committed: i64,
+    pub approved: i64,
+    pub pending_issues: i64,
+}
+
+struct BudgetState {
+    budget: Option<i32>,
+    approved: Option<bool>,
+}
+
+async fn lock_budget(
+    tx: &mut Transaction<'_, Postgres>,
+    issue_id: &str,
+) -> anyhow::Result<BudgetState> {
+    let rec = sqlx::query!(
+        r#"
+        SELECT issue_budget, issue_budget_approved
+        FROM issues
+        WHERE issue_id = $1
+        FOR UPDATE
+        "#,
+        issue_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| anyhow!("issue {issue_id} is not tracked"))?;
+
+    Ok(BudgetState {
+        budget: rec.issue_budget,
+        approved: rec.issue_budget_approved,
+    })
+}
+
+// Rejects `new_budget` if it would push the project's committed total past its `budget_cap`.
+// Locks the project row so concurrent proposals on the same project are checked in turn.
+async fn check_budget_cap(
+    tx: &mut Transaction<'_, Postgres>,
+    issue_id: &str,
+    new_budget: i32,
+) -> anyhow::Result<()> {
+    let rec = sqlx::query!(
+        r#"
+        SELECT p.project_id,
+               p.budget_cap,
+               (SELECT COALESCE(SUM(o.issue_budget) FILTER (WHERE o.issue_budget_approved IS DISTINCT FROM FALSE), 0)
+                FROM issues o
+                WHERE o.project_id = p.project_id AND o.issue_id <> $1)::BIGINT AS "committed!"
+        FROM projects p
+        JOIN issues i ON i.project_id = p.project_id
+        WHERE i.issue_id = $1
+        FOR UPDATE OF p
+        "#,
+        issue_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    if let Some(cap) = rec.budget_cap {
+        let total = rec.committed + i64::from(new_budget);
+        if total > i64::from(cap) {
+            return Err(anyhow!(
+                "budget of {new_budget} would commit {total} on {}, over its cap of {cap}",
+                rec.project_id
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn log_budget_event(
+    tx: &mut Transaction<'_, Postgres>,
+    issue_id: &str,
+    action: BudgetAction,
+    old_budget: Option<i32>,
+    new_budget: Option<i32>,
+    actor: &str,
+    note: Option<&str>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_budget_events (issue_id, action, old_budget, new_budget, actor, note)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        issue_id,
+        action.as_str(),
+        old_budget,
+        new_budget,
+        actor,
+        note
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+pub async fn propose_budget(
+    pool: &PgPool,
+    issue_id: &str,
+    amount: i32,
+    proposer: &str,
+    note: Option<&str>,
+) -> anyhow::Result<()> {
+    if amount <= 0 {
+        return Err(anyhow!("budget must be positive, got {amount}"));
+    }
+
+    let mut tx = pool.begin().await?;
+    let state = lock_budget(&mut tx, issue_id).await?;
+    if state.budget.is_some() {
+        return Err(anyhow!(
+            "issue {issue_id} already has a budget of {:?}, use change_budget",
+            state.budget
+        ));
+    }
+    check_budget_cap(&mut tx, issue_id, amount).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE issues
+        SET issue_budget = $2,
+            issue_budget_approved = NULL,
+            issue_budget_reviewed_by = NULL,
+            issue_budget_reviewed_at = NULL,
+            updated_at = now()
+        WHERE issue_id = $1
+        "#,
+        issue_id,
+        amount
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    log_budget_event(
+        &mut tx,
+        issue_id,
+        BudgetAction::Propose,
+        None,
+        Some(amount),
+        proposer,
+        note,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn review_budget(
+    pool: &PgPool,
+    issue_id: &str,
+    approve: bool,
+    reviewer: &str,
+    note: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    let state = lock_budget(&mut tx, issue_id).await?;
+    let Some(budget) = state.budget else {
+        return Err(anyhow!("issue {issue_id} has no proposed budget"));
+    };
+    if state.approved.is_some() {
+        return Err(anyhow!(
+            "budget for issue {issue_id} was already {}",
+            if state.approved == Some(true) {
+                "approved"
+            } else {
+                "rejected"
+            }
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE issues
+        SET issue_budget_approved = $2,
+            issue_budget_reviewed_by = $3,
+            issue_budget_reviewed_at = now(),
+            updated_at = now()
+        WHERE issue_id = $1
+        "#,
+        issue_id,
+        approve,
+        reviewer
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let action = if approve {
+        BudgetAction::Approve
+    } else {
+        BudgetAction::Reject
+    };
+    log_budget_event(
+        &mut tx,
+        issue_id,
+        action,
+        Some(budget),
+        Some(budget),
+        reviewer,
+        note,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn approve_budget(pool: &PgPool, issue_id: &str, approver: &str) -> anyhow::Result<()> {
+    review_budget(pool, issue_id, true, approver, None).await
+}
+
+pub async fn reject_budget(
+    pool: &PgPool,
+    issue_id: &str,
+    approver: &str,
+    reason: &str,
+) -> anyhow::Result<()> {
+    review_budget(pool, issue_id, false, approver, Some(reason)).await
+}
+
+// A changed amount goes back to pending, even if the old one had been approved.
+pub async fn change_budget(
+    pool: &PgPool,
+    issue_id: &str,
+    new_amount: i32,
+    actor: &str,
+    reason: &str,
+) -> anyhow::Result<()> {
+    if new_amount <= 0 {
+        return Err(anyhow!("budget must be positive, got {new_amount}"));
+    }
+    if reason.trim().is_empty() {
+        return Err(anyhow!("a reason is required to change a budget"));
+    }
+
+    let mut tx = pool.begin().await?;
+    let state = lock_budget(&mut tx, issue_id).await?;
+    if state.budget.is_none() {
+        return Err(anyhow!(
+            "issue {issue_id} has no budget yet, use propose_budget"
+        ));
+    }
+    if state.budget == Some(new_amount) {
+        return Ok(());
+    }
+    check_budget_cap(&mut tx, issue_id, new_amount).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE issues
+        SET issue_budget = $2,
+            issue_budget_approved = NULL,
+            issue_budget_reviewed_by = NULL,
+            issue_budget_reviewed_at = NULL,
+            updated_at = now()
+        WHERE issue_id = $1
+        "#,
+        issue_id,
+        new_amount
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    log_budget_event(
+        &mut tx,
+        issue_id,
+        BudgetAction::Change,
+        state.budget,
+        Some(new_amount),
+        actor,
+        Some(reason),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn budget_history(pool: &PgPool, issue_id: &str) -> anyhow::Result<Vec<BudgetEvent>> {
+    let events = sqlx::query_as::<_, BudgetEvent>(
+        r#"
+        SELECT *
+        FROM issue_budget_events
+        WHERE issue_id = $1
+        ORDER BY created_at, event_id
+        "#,
+    )
+    .bind(issue_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
+
+// `committed` counts every budget that has not been rejected; `approved` only the approved ones.
+pub async fn project_budget_summary(pool: &PgPool) -> anyhow::Result<Vec<ProjectBudgetSummary>> {
+    let summary = sqlx::query_as::<_, ProjectBudgetSummary>(
+        r#"
+        SELECT project_id,
+               COALESCE(SUM(issue_budget) FILTER (WHERE issue_budget_approved IS DISTINCT FROM FALSE), 0)::BIGINT AS committed,
+               COALESCE(SUM(issue_budget) FILTER (WHERE issue_budget_approved), 0)::BIGINT AS approved,
+               COUNT(*) FILTER (WHERE issue_budget IS NOT NULL AND issue_budget_approved IS NULL) AS pending_issues
+        FROM issues
+        GROUP BY project_id
+        ORDER BY project_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(summary)
+}
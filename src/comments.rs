@@ -0,0 +1,256 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::db_updater::{list_issues, upsert_comment, UpsertOutcome};
+use crate::graphql::parse_graphql;
+use crate::issues_tracker::github_http_post_gql;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IssueComment {
+    pub url: String,
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// `complete` is false when GitHub returned a null in place of a comment or one of its
+// required fields, so `comments` cannot be used to prune the stored ones.
+#[derive(Clone, Debug, Default)]
+pub struct CommentsPage {
+    pub comments: Vec<IssueComment>,
+    pub complete: bool,
+    pub end_cursor: Option<String>,
+    pub has_next_page: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IssueComments {
+    pub comments: Vec<IssueComment>,
+    pub complete: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommentSync {
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub deleted: usize,
+}
+
+// One page of up to 100 comments on the issue at `issue_url`, starting after `after`.
+pub async fn fetch_comments_page(
+    issue_url: &str,
+    after: Option<&str>,
+) -> anyhow::Result<CommentsPage> {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Data {
+        resource: Option<Resource>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Resource {
+        comments: Option<Comments>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Comments {
+        nodes: Option<Vec<Option<CommentNode>>>,
+        pageInfo: PageInfo,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct CommentNode {
+        url: Option<String>,
+        author: Option<Author>,
+        body: Option<String>,
+        createdAt: Option<String>,
+        updatedAt: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Author {
+        login: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct PageInfo {
+        endCursor: Option<String>,
+        hasNextPage: bool,
+    }
+
+    let query_str = r#"
+        query ($url: URI!, $after: String) {
+            resource(url: $url) {
+                ... on Issue {
+                    comments(first: 100, after: $after) {
+                        nodes {
+                            url
+                            author {
+                                login
+                            }
+                            body
+                            createdAt
+                            updatedAt
+                        }
+                        pageInfo {
+                            endCursor
+                            hasNextPage
+                        }
+                    }
+                }
+            }
+            rateLimit {
+                cost
+                remaining
+                resetAt
+            }
+        }
+        "#;
+
+    let variables = serde_json::json!({ "url": issue_url, "after": after });
+    let response_body = github_http_post_gql(query_str, variables)
+        .await
+        .map_err(|e| anyhow!("Failed to post GraphQL query: {}", e))?;
+
+    let data: Data = parse_graphql(&response_body)?;
+    let Some(comments) = data.resource.and_then(|r| r.comments) else {
+        return Err(anyhow!("{issue_url} is not an issue"));
+    };
+
+    let Some(nodes) = comments.nodes else {
+        return Ok(CommentsPage {
+            end_cursor: comments.pageInfo.endCursor,
+            has_next_page: comments.pageInfo.hasNextPage,
+            ..CommentsPage::default()
+        });
+    };
+    let fetched = nodes.len();
+    let page: Vec<IssueComment> = nodes
+        .into_iter()
+        .filter_map(|c| {
+            let c = c?;
+            Some(IssueComment {
+                url: c.url?,
+                // Comments from deleted accounts have no author.
+                author: c
+                    .author
+                    .and_then(|a| a.login)
+                    .unwrap_or_else(|| "ghost".to_string()),
+                body: c.body.unwrap_or_default(),
+                created_at: c.createdAt?,
+                updated_at: c.updatedAt?,
+            })
+        })
+        .collect();
+
+    Ok(CommentsPage {
+        complete: page.len() == fetched,
+        comments: page,
+        end_cursor: comments.pageInfo.endCursor,
+        has_next_page: comments.pageInfo.hasNextPage,
+    })
+}
+
+pub async fn fetch_issue_comments(issue_url: &str) -> anyhow::Result<IssueComments> {
+    fetch_issue_comments_from(issue_url, None).await
+}
+
+// Pages through the rest of the comments after `after`, e.g. once a search hit's own
+// comment page reports more.
+pub async fn fetch_issue_comments_from(
+    issue_url: &str,
+    after: Option<String>,
+) -> anyhow::Result<IssueComments> {
+    let mut all_comments = IssueComments {
+        comments: Vec::new(),
+        complete: true,
+    };
+    let mut after_cursor = after;
+
+    loop {
+        let page = fetch_comments_page(issue_url, after_cursor.as_deref()).await?;
+        all_comments.comments.extend(page.comments);
+        all_comments.complete &= page.complete;
+        match page.end_cursor {
+            Some(cursor) if page.has_next_page => after_cursor = Some(cursor),
+            // More pages without a cursor to reach them.
+            _ => {
+                all_comments.complete &= !page.has_next_page;
+                break;
+            }
+        }
+    }
+
+    Ok(all_comments)
+}
+
+// Makes the stored comments of `issue_id` match `comments`. Edited comments are updated in
+// place. Comments missing from the list are deleted only when `complete` says the list holds
+// every comment on the issue; otherwise a comment GitHub failed to return would be lost.
+pub async fn sync_issue_comments(
+    pool: &PgPool,
+    issue_id: &str,
+    comments: &[IssueComment],
+    complete: bool,
+) -> anyhow::Result<CommentSync> {
+    let mut tx = pool.begin().await?;
+    let mut sync = CommentSync::default();
+
+    for comment in comments {
+        match upsert_comment(&mut *tx, issue_id, comment).await? {
+            UpsertOutcome::Inserted => sync.inserted += 1,
+            UpsertOutcome::Updated => sync.updated += 1,
+            UpsertOutcome::Unchanged => sync.unchanged += 1,
+        }
+    }
+
+    if complete {
+        let seen: Vec<String> = comments.iter().map(|c| c.url.clone()).collect();
+        sync.deleted = sqlx::query!(
+            r#"
+            DELETE FROM comments
+            WHERE issue_id = $1 AND comment_id <> ALL($2)
+            "#,
+            issue_id,
+            &seen
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as usize;
+    } else {
+        log::warn!("comments on {issue_id} came back incomplete, keeping the stored ones");
+    }
+
+    tx.commit().await?;
+    Ok(sync)
+}
+
+// The issue row must already exist.
+pub async fn ingest_issue_comments(pool: &PgPool, issue_id: &str) -> anyhow::Result<CommentSync> {
+    let fetched = fetch_issue_comments(issue_id).await?;
+    let sync = sync_issue_comments(pool, issue_id, &fetched.comments, fetched.complete).await?;
+    log::info!("comments on {issue_id}: {sync:?}");
+    Ok(sync)
+}
+
+// A failing issue is logged and skipped so one bad issue does not stop the project.
+pub async fn ingest_project_comments(
+    pool: &PgPool,
+    project_id: &str,
+) -> anyhow::Result<CommentSync> {
+    let mut total = CommentSync::default();
+    for issue in list_issues(pool, project_id).await? {
+        match ingest_issue_comments(pool, &issue.issue_id).await {
+            Ok(sync) => {
+                total.inserted += sync.inserted;
+                total.updated += sync.updated;
+                total.unchanged += sync.unchanged;
+                total.deleted += sync.deleted;
+            }
+            Err(e) => log::error!("failed to ingest comments on {}: {e}", issue.issue_id),
+        }
+    }
+    Ok(total)
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::date_windows::DateWindow;
+use crate::registry::{project_config_query, ProjectConfig};
+use crate::slack::send_slack_message;
+
+pub const DEFAULT_LEADERBOARD_SIZE: i64 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Contributor {
+    pub login: String,
+    pub issues_opened: i32,
+    pub prs_merged: i32,
+    pub bounty_earned: i64,
+    pub projects_touched: i32,
+    pub first_activity_at: Option<DateTime<Utc>>,
+    pub last_activity_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub login: String,
+    pub issues_opened: i64,
+    pub prs_merged: i64,
+    pub bounty_earned: i64,
+}
+
+// Recomputes every contributor row from the `contributor_activity` view. Logins with no
+// activity left (e.g. after a PR row was removed) are dropped.
+pub async fn refresh_contributors(pool: &PgPool) -> anyhow::Result<u64> {
+    let mut tx = pool.begin().await?;
+
+    let changed = sqlx::query!(
+        r#"
+        INSERT INTO contributors (login, issues_opened, prs_merged, bounty_earned, projects_touched, first_activity_at, last_activity_at)
+        SELECT login,
+               SUM(issues_opened)::INT,
+               SUM(prs_merged)::INT,
+               SUM(bounty)::BIGINT,
+               COUNT(DISTINCT project_id)::INT,
+               MIN(activity_at),
+               MAX(activity_at)
+        FROM contributor_activity
+        GROUP BY login
+        ON CONFLICT (login) DO UPDATE
+        SET issues_opened = EXCLUDED.issues_opened,
+            prs_merged = EXCLUDED.prs_merged,
+            bounty_earned = EXCLUDED.bounty_earned,
+            projects_touched = EXCLUDED.projects_touched,
+            first_activity_at = EXCLUDED.first_activity_at,
+            last_activity_at = EXCLUDED.last_activity_at,
+            updated_at = now()
+        WHERE (contributors.issues_opened, contributors.prs_merged, contributors.bounty_earned, contributors.projects_touched, contributors.first_activity_at, contributors.last_activity_at)
+            IS DISTINCT FROM
+            (EXCLUDED.issues_opened, EXCLUDED.prs_merged, EXCLUDED.bounty_earned, EXCLUDED.projects_touched, EXCLUDED.first_activity_at, EXCLUDED.last_activity_at)
+        "#
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let removed = sqlx::query!(
+        r#"
+        DELETE FROM contributors c
+        WHERE NOT EXISTS (SELECT 1 FROM contributor_activity a WHERE a.login = c.login)
+        "#
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok(changed + removed)
+}
+
+pub async fn get_contributor(pool: &PgPool, login: &str) -> anyhow::Result<Option<Contributor>> {
+    let contributor = sqlx::query_as::<_, Contributor>(
+        r#"
+        SELECT *
+        FROM contributors
+        WHERE login = $1
+        "#,
+    )
+    .bind(login)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(contributor)
+}
+
+pub async fn list_contributors(pool: &PgPool) -> anyhow::Result<Vec<Contributor>> {
+    let contributors = sqlx::query_as::<_, Contributor>(
+        r#"
+        SELECT *
+        FROM contributors
+        ORDER BY bounty_earned DESC, prs_merged DESC, issues_opened DESC, login
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(contributors)
+}
+
+// Ranks contributors by what they did inside `window`: bounty earned first, then merged PRs,
+// then opened issues. Ties share a rank. `project_id` narrows it to one project.
+pub async fn leaderboard(
+    pool: &PgPool,
+    window: &DateWindow,
+    project_id: Option<&str>,
+    limit: i64,
+) -> anyhow::Result<Vec<LeaderboardEntry>> {
+    let entries = sqlx::query_as::<_, LeaderboardEntry>(
+        r#"
+        SELECT RANK() OVER (ORDER BY SUM(bounty) DESC, SUM(prs_merged) DESC, SUM(issues_opened) DESC) AS rank,
+               login,
+               SUM(issues_opened)::BIGINT AS issues_opened,
+               SUM(prs_merged)::BIGINT AS prs_merged,
+               SUM(bounty)::BIGINT AS bounty_earned
+        FROM contributor_activity
+        WHERE activity_at >= $1
+          AND activity_at < $2
+          AND ($3::VARCHAR IS NULL OR project_id = $3)
+        GROUP BY login
+        ORDER BY rank, login
+        LIMIT $4
+        "#,
+    )
+    .bind(window.start)
+    .bind(window.end)
+    .bind(project_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+pub fn format_leaderboard(title: &str, entries: &[LeaderboardEntry]) -> String {
+    let mut text = format!("{title}\n");
+    if entries.is_empty() {
+        text.push_str("No contributions in this period.\n");
+    }
+    for entry in entries {
+        text.push_str(&format!(
+            "{}. {}: {} bounty, {} merged PRs, {} issues opened\n",
+            entry.rank, entry.login, entry.bounty_earned, entry.prs_merged, entry.issues_opened
+        ));
+    }
+    text
+}
+
+// Posts the final leaderboard of every project whose active range has ended, once. Projects
+// without a start date or Slack workspace and channel are skipped.
+pub async fn post_event_leaderboards(pool: &PgPool) -> anyhow::Result<usize> {
+    let ended = sqlx::query_as::<_, ProjectConfig>(&project_config_query(
+        r#"
+        WHERE active_until <= now()
+          AND active_from IS NOT NULL
+          AND slack_workspace IS NOT NULL
+          AND slack_channel IS NOT NULL
+          AND leaderboard_posted_at IS NULL
+        ORDER BY active_until, project_id
+        "#,
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    let mut posted = 0;
+    for project in ended {
+        let (Some(start), Some(end), Some((workspace, channel))) = (
+            project.active_from,
+            project.active_until,
+            project.slack_target(),
+        ) else {
+            continue;
+        };
+        // One project's failure is logged so the others still get their leaderboard.
+        let entries = async {
+            let window = DateWindow::new(start, end)?;
+            leaderboard(
+                pool,
+                &window,
+                Some(&project.project_id),
+                DEFAULT_LEADERBOARD_SIZE,
+            )
+            .await
+        }
+        .await;
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::error!("failed to rank contributors of {}: {e}", project.project_id);
+                continue;
+            }
+        };
+        let title = format!(
+            "Final leaderboard for {} ({} to {})",
+            project.project_id,
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d")
+        );
+        // Left unmarked on failure so the next run tries again.
+        let text = format_leaderboard(&title, &entries);
+        if let Err(e) = send_slack_message(workspace, channel, &text).await {
+            log::error!("failed to post leaderboard of {}: {e}", project.project_id);
+            continue;
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE projects
+            SET leaderboard_posted_at = now(), updated_at = now()
+            WHERE project_id = $1
+            "#,
+            project.project_id
+        )
+        .execute(pool)
+        .await?;
+        posted += 1;
+    }
+    Ok(posted)
+}
@@ -0,0 +1,235 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::date_windows::DateWindow;
+use crate::issues_tracker::{
+    search_issues_in_window, search_pull_requests_in_window, OuterIssue, OuterPull,
+};
+use crate::search_query::{DateField, SearchQuery};
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct CrawlState {
+    pub query_key: String,
+    pub last_window_start: DateTime<Utc>,
+    pub last_window_end: DateTime<Utc>,
+    pub updated_high_water: DateTime<Utc>,
+}
+
+// Where crawl progress is kept between scheduled runs. `Flows` uses the flows.network
+// key-value store for deployments that have no database attached.
+pub enum CrawlStateStore {
+    Postgres(PgPool),
+    Flows,
+}
+
+impl CrawlStateStore {
+    pub async fn load(&self, query_key: &str) -> anyhow::Result<Option<CrawlState>> {
+        match self {
+            CrawlStateStore::Postgres(pool) => {
+                let state = sqlx::query_as::<_, CrawlState>(
+                    r#"
+                    SELECT query_key, last_window_start, last_window_end, updated_high_water
+                    FROM crawl_state
+                    WHERE query_key = $1
+                    "#,
+                )
+                .bind(query_key)
+                .fetch_optional(pool)
+                .await?;
+                Ok(state)
+            }
+            CrawlStateStore::Flows => match store_flows::get(&flows_key(query_key)) {
+                Some(value) => Ok(Some(serde_json::from_value(value)?)),
+                None => Ok(None),
+            },
+        }
+    }
+
+    pub async fn save(&self, state: &CrawlState) -> anyhow::Result<()> {
+        match self {
+            CrawlStateStore::Postgres(pool) => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO crawl_state (query_key, last_window_start, last_window_end, updated_high_water)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (query_key) DO UPDATE
+                    SET last_window_start = EXCLUDED.last_window_start,
+                        last_window_end = EXCLUDED.last_window_end,
+                        updated_high_water = EXCLUDED.updated_high_water,
+                        updated_at = now()
+                    "#,
+                    state.query_key,
+                    state.last_window_start,
+                    state.last_window_end,
+                    state.updated_high_water
+                )
+                .execute(pool)
+                .await?;
+            }
+            CrawlStateStore::Flows => {
+                store_flows::set(
+                    &flows_key(&state.query_key),
+                    serde_json::to_value(state)?,
+                    None,
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn flows_key(query_key: &str) -> String {
+    format!("crawl_state:{query_key}")
+}
+
+// How far behind now a window ends. GitHub's search index lags behind writes, so items
+// updated in the last few minutes may not match yet; they are picked up by the next run.
+const SEARCH_INDEX_LAG_MINUTES: i64 = 5;
+
+// The window to fetch next: from the stored high-water mark (or `initial_start` on the
+// first run) up to a little before now. The mark itself is included, duplicates are
+// absorbed by the upserts.
+async fn next_window(
+    store: &CrawlStateStore,
+    query_key: &str,
+    initial_start: DateTime<Utc>,
+) -> anyhow::Result<Option<DateWindow>> {
+    let since = store
+        .load(query_key)
+        .await?
+        .map_or(initial_start, |state| state.updated_high_water);
+    let until = Utc::now() - Duration::minutes(SEARCH_INDEX_LAG_MINUTES);
+    if since >= until {
+        return Ok(None);
+    }
+    Ok(Some(DateWindow::new(since, until)?))
+}
+
+// The latest `updatedAt` among `updated_at`, capped at the window end: an item edited again
+// after the search ran reports a later time than the window it matched in, and jumping past
+// the window end would skip whatever was updated in between.
+fn high_water_mark<'a>(
+    window: DateWindow,
+    updated_at: impl Iterator<Item = &'a str>,
+) -> DateTime<Utc> {
+    updated_at
+        .filter_map(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .max()
+        .map_or(window.start, |latest| {
+            latest.clamp(window.start, window.end)
+        })
+}
+
+// Items fetched by one incremental run, plus the state to save once they have been handled.
+// The next run starts from the latest update actually seen, or from the same start when
+// nothing matched.
+pub struct IncrementalBatch<T> {
+    pub items: Vec<T>,
+    pub checkpoint: Option<CrawlState>,
+}
+
+impl<T> IncrementalBatch<T> {
+    fn empty() -> Self {
+        IncrementalBatch {
+            items: Vec::new(),
+            checkpoint: None,
+        }
+    }
+
+    fn covering(
+        query_key: String,
+        window: DateWindow,
+        updated_high_water: DateTime<Utc>,
+        items: Vec<T>,
+    ) -> Self {
+        IncrementalBatch {
+            items,
+            checkpoint: Some(CrawlState {
+                query_key,
+                last_window_start: window.start,
+                last_window_end: window.end,
+                updated_high_water,
+            }),
+        }
+    }
+
+    // Call only after the items were stored, so a failed run is retried from the old mark.
+    pub async fn commit(&self, store: &CrawlStateStore) -> anyhow::Result<()> {
+        match &self.checkpoint {
+            Some(state) => store.save(state).await,
+            None => Ok(()),
+        }
+    }
+}
+
+// Fetches only issues updated since the previous committed run of the same query.
+pub async fn search_issues_incremental(
+    store: &CrawlStateStore,
+    base: &SearchQuery,
+    initial_start: DateTime<Utc>,
+) -> anyhow::Result<IncrementalBatch<OuterIssue>> {
+    let query_key = format!("issues:{base}");
+    let Some(window) = next_window(store, &query_key, initial_start).await? else {
+        return Ok(IncrementalBatch::empty());
+    };
+
+    let issues = search_issues_in_window(base, DateField::Updated, window).await?;
+    let high_water = high_water_mark(window, issues.iter().map(|i| i.updated_at.as_str()));
+    Ok(IncrementalBatch::covering(
+        query_key, window, high_water, issues,
+    ))
+}
+
+pub async fn search_pull_requests_incremental(
+    store: &CrawlStateStore,
+    base: &SearchQuery,
+    initial_start: DateTime<Utc>,
+) -> anyhow::Result<IncrementalBatch<OuterPull>> {
+    let query_key = format!("pulls:{base}");
+    let Some(window) = next_window(store, &query_key, initial_start).await? else {
+        return Ok(IncrementalBatch::empty());
+    };
+
+    let pulls = search_pull_requests_in_window(base, DateField::Updated, window).await?;
+    let high_water = high_water_mark(window, pulls.iter().map(|p| p.updated_at.as_str()));
+    Ok(IncrementalBatch::covering(
+        query_key, window, high_water, pulls,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn window() -> DateWindow {
+        DateWindow::new(
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn high_water_mark_is_the_latest_update_seen() {
+        let mark = high_water_mark(
+            window(),
+            ["2024-03-01T08:00:00Z", "2024-03-01T12:30:00Z", "not a time"].into_iter(),
+        );
+        assert_eq!(mark, Utc.with_ymd_and_hms(2024, 3, 1, 12, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn high_water_mark_stays_inside_the_window() {
+        assert_eq!(
+            high_water_mark(window(), std::iter::empty()),
+            window().start
+        );
+        assert_eq!(
+            high_water_mark(window(), ["2024-03-05T00:00:00Z"].into_iter()),
+            window().end
+        );
+    }
+}
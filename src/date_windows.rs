@@ -0,0 +1,231 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::search_query::DateRange;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stride {
+    Days(i64),
+    Hours(i64),
+}
+
+impl Stride {
+    pub fn to_duration(self) -> anyhow::Result<Duration> {
+        let duration = match self {
+            Stride::Days(n) | Stride::Hours(n) if n <= 0 => None,
+            Stride::Days(n) => Duration::try_days(n),
+            Stride::Hours(n) => Duration::try_hours(n),
+        };
+        duration.ok_or_else(|| anyhow!("stride must be positive and in range, got {:?}", self))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowEnd {
+    At(DateTime<Utc>),
+    Now,
+}
+
+/// A half-open `[start, end)` slice of time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DateWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl DateWindow {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> anyhow::Result<Self> {
+        if end <= start {
+            return Err(anyhow!("window end {end} is not after start {start}"));
+        }
+        Ok(DateWindow { start, end })
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    // GitHub ranges are inclusive, so the last second is dropped to keep windows from overlapping.
+    pub fn to_search_range(&self) -> DateRange {
+        DateRange::Between(self.start.into(), (self.end - Duration::seconds(1)).into())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateWindowPlan {
+    pub start: DateTime<Utc>,
+    pub end: WindowEnd,
+    pub stride: Stride,
+}
+
+impl DateWindowPlan {
+    pub fn new(start: &str, end: Option<&str>, stride: Stride) -> anyhow::Result<Self> {
+        let start = parse_search_date(start)?;
+        let end = match end {
+            Some(end) => WindowEnd::At(parse_search_date(end)?),
+            None => WindowEnd::Now,
+        };
+        stride.to_duration()?;
+        Ok(DateWindowPlan { start, end, stride })
+    }
+
+    // The whole `[start, end)` range the plan covers, resolving `WindowEnd::Now` to the current time.
+    pub fn span(&self) -> anyhow::Result<DateWindow> {
+        let end = match self.end {
+            WindowEnd::At(end) => end,
+            WindowEnd::Now => Utc::now(),
+        };
+        if end <= self.start {
+            return Err(anyhow!(
+                "plan end {end} is not after plan start {}",
+                self.start
+            ));
+        }
+        Ok(DateWindow {
+            start: self.start,
+            end,
+        })
+    }
+
+    // Open-ended plans render as `>=start` so the query text stays the same from run to run.
+    pub fn to_search_range(&self) -> DateRange {
+        match self.end {
+            WindowEnd::At(end) => DateWindow {
+                start: self.start,
+                end,
+            }
+            .to_search_range(),
+            WindowEnd::Now => DateRange::OnOrAfter(self.start.into()),
+        }
+    }
+
+    pub fn windows(&self) -> anyhow::Result<Vec<DateWindow>> {
+        let DateWindow { start, end } = self.span()?;
+        let stride = self.stride.to_duration()?;
+
+        let mut out = Vec::new();
+        let mut cursor = start;
+        while cursor < end {
+            let next = cursor
+                .checked_add_signed(stride)
+                .ok_or_else(|| anyhow!("stride {:?} overflows after {cursor}", self.stride))?;
+            let next = std::cmp::min(next, end);
+            out.push(DateWindow {
+                start: cursor,
+                end: next,
+            });
+            cursor = next;
+        }
+        Ok(out)
+    }
+}
+
+// Accepts either a bare `YYYY-MM-DD` day (taken as midnight UTC) or an RFC 3339 timestamp.
+pub fn parse_search_date(input: &str) -> anyhow::Result<DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(day) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let midnight = day
+            .and_hms_opt(0, 0, 0)
+            .ok_or_else(|| anyhow!("invalid date: {input}"))?;
+        return Ok(DateTime::from_naive_utc_and_offset(midnight, Utc));
+    }
+    DateTime::parse_from_rfc3339(input)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| anyhow!("invalid date {input:?}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 10, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn windows_tile_the_span_without_gaps() {
+        let plan = DateWindowPlan::new("2023-10-01", Some("2023-10-07"), Stride::Days(2)).unwrap();
+        let windows = plan.windows().unwrap();
+
+        assert_eq!(
+            windows,
+            vec![
+                DateWindow::new(at(1, 0), at(3, 0)).unwrap(),
+                DateWindow::new(at(3, 0), at(5, 0)).unwrap(),
+                DateWindow::new(at(5, 0), at(7, 0)).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn last_window_is_cut_at_the_plan_end() {
+        let plan = DateWindowPlan::new(
+            "2023-10-01T00:00:00Z",
+            Some("2023-10-02T05:00:00Z"),
+            Stride::Hours(12),
+        )
+        .unwrap();
+        let windows = plan.windows().unwrap();
+
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[2], DateWindow::new(at(2, 0), at(2, 5)).unwrap());
+        assert_eq!(windows.first().unwrap().start, at(1, 0));
+        assert_eq!(windows.last().unwrap().end, at(2, 5));
+    }
+
+    #[test]
+    fn stride_longer_than_the_span_gives_one_window() {
+        let plan = DateWindowPlan::new("2023-10-01", Some("2023-10-02"), Stride::Days(30)).unwrap();
+        assert_eq!(
+            plan.windows().unwrap(),
+            vec![DateWindow::new(at(1, 0), at(2, 0)).unwrap()]
+        );
+    }
+
+    #[test]
+    fn empty_or_reversed_plans_are_rejected() {
+        let same = DateWindowPlan::new("2023-10-01", Some("2023-10-01"), Stride::Days(1)).unwrap();
+        assert!(same.windows().is_err());
+
+        let reversed =
+            DateWindowPlan::new("2023-10-02", Some("2023-10-01"), Stride::Days(1)).unwrap();
+        assert!(reversed.windows().is_err());
+
+        assert!(DateWindowPlan::new("2023-10-01", None, Stride::Days(0)).is_err());
+        assert!(DateWindowPlan::new("2023-10-01", None, Stride::Hours(-1)).is_err());
+    }
+
+    #[test]
+    fn huge_strides_are_errors_instead_of_panics() {
+        assert!(DateWindowPlan::new("2023-10-01", None, Stride::Days(i64::MAX)).is_err());
+        assert!(DateWindowPlan::new("2023-10-01", None, Stride::Hours(i64::MAX)).is_err());
+
+        let plan = DateWindowPlan::new(
+            "2023-10-01",
+            Some("2023-10-02"),
+            Stride::Days(1_000_000_000),
+        )
+        .unwrap();
+        assert!(plan.windows().is_err());
+    }
+
+    #[test]
+    fn search_ranges_of_adjacent_windows_do_not_overlap() {
+        let plan = DateWindowPlan::new("2023-10-01", Some("2023-10-03"), Stride::Days(1)).unwrap();
+        let ranges = plan
+            .windows()
+            .unwrap()
+            .iter()
+            .map(|w| w.to_search_range().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            ranges,
+            vec![
+                "2023-10-01T00:00:00Z..2023-10-01T23:59:59Z",
+                "2023-10-02T00:00:00Z..2023-10-02T23:59:59Z",
+            ]
+        );
+    }
+}
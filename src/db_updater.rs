@@ -1,402 +1,445 @@
-/* use crate::issues_tracker::get_project_logo;
-use sqlx::{postgres::PgPool, sqlx_macros};
-use std::env;
-
-pub async fn project_exists(pool: &PgPool, project_id: &str) -> anyhow::Result<bool> {
-    let exists = sqlx::query!(
-        "SELECT EXISTS(SELECT 1 FROM projects WHERE project_id = $1) AS 'exists!'",
-        project_id
-    )
-    .fetch_one(pool)
-    .await?
-    .exists;
+use crate::comments::IssueComment;
+use crate::issues_tracker::{get_project_logo, parse_github_url, OuterIssue, OuterPull};
+use crate::lifecycle::refresh_issue_lifecycle;
+use crate::review::ReviewStatus;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgExecutor, PgPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    Inserted,
+    Updated,
+    Unchanged,
+}
 
-    Ok(exists)
+impl UpsertOutcome {
+    // Every upsert below ends in `RETURNING (xmax = 0) AS "inserted!"` and only returns a row
+    // when it wrote one, so no row means the stored values already matched.
+    fn from_returned(inserted: Option<bool>) -> Self {
+        match inserted {
+            Some(true) => UpsertOutcome::Inserted,
+            Some(false) => UpsertOutcome::Updated,
+            None => UpsertOutcome::Unchanged,
+        }
+    }
 }
-pub async fn add_project_with_check(
+
+pub async fn upsert_project(
     pool: &PgPool,
     project_id: &str,
     project_logo: &str,
-) -> anyhow::Result<()> {
-    if project_exists(pool, project_id).await? {
-        return Err(anyhow::anyhow!(
-            "Project with ID '{}' already exists.",
-            project_id
-        ));
-    }
-
-    sqlx::query!(
+) -> anyhow::Result<UpsertOutcome> {
+    let inserted = sqlx::query!(
         r#"
         INSERT INTO projects (project_id, project_logo)
         VALUES ($1, $2)
+        ON CONFLICT (project_id) DO UPDATE
+        SET project_logo = EXCLUDED.project_logo,
+            updated_at = now()
+        WHERE projects.project_logo IS DISTINCT FROM EXCLUDED.project_logo
+        RETURNING (xmax = 0) AS "inserted!"
         "#,
         project_id,
         project_logo
     )
-    .execute(pool)
-    .await?;
-    Ok(())
+    .fetch_optional(pool)
+    .await?
+    .map(|r| r.inserted);
+
+    Ok(UpsertOutcome::from_returned(inserted))
 }
 
-pub async fn add_project(
+#[allow(clippy::too_many_arguments)]
+pub async fn upsert_issue(
     pool: &PgPool,
+    issue_id: &str,
     project_id: &str,
-    project_logo: &str,
-) -> anyhow::Result<()> {
-    sqlx::query!(
+    title: &str,
+    description: &str,
+    state: &str,
+    state_reason: Option<&str>,
+    labels: &[String],
+    author: &str,
+    opened_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<UpsertOutcome> {
+    let inserted = sqlx::query!(
         r#"
-        INSERT INTO projects (project_id, project_logo)
-        VALUES ($1, $2)
+        INSERT INTO issues (issue_id, project_id, issue_title, issue_description, issue_state, issue_state_reason, issue_labels, issue_author, issue_opened_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (issue_id) DO UPDATE
+        SET project_id = EXCLUDED.project_id,
+            issue_title = EXCLUDED.issue_title,
+            issue_description = EXCLUDED.issue_description,
+            issue_state = EXCLUDED.issue_state,
+            issue_state_reason = EXCLUDED.issue_state_reason,
+            issue_labels = EXCLUDED.issue_labels,
+            issue_author = EXCLUDED.issue_author,
+            issue_opened_at = EXCLUDED.issue_opened_at,
+            updated_at = now()
+        WHERE (issues.project_id, issues.issue_title, issues.issue_description, issues.issue_state, issues.issue_state_reason, issues.issue_labels, issues.issue_author, issues.issue_opened_at)
+            IS DISTINCT FROM
+            (EXCLUDED.project_id, EXCLUDED.issue_title, EXCLUDED.issue_description, EXCLUDED.issue_state, EXCLUDED.issue_state_reason, EXCLUDED.issue_labels, EXCLUDED.issue_author, EXCLUDED.issue_opened_at)
+        RETURNING (xmax = 0) AS "inserted!"
         "#,
+        issue_id,
         project_id,
-        project_logo
+        title,
+        description,
+        state,
+        state_reason,
+        labels,
+        author,
+        opened_at,
     )
-    .execute(pool)
-    .await?;
-    Ok(())
-}
-
-pub async fn add_project_test_1(pool: &PgPool) -> anyhow::Result<()> {
-    let project_id = "https://github.com/jaykchen/issue-labeler";
-    let project_logo = "https://avatars.githubusercontent.com/u/112579101?v=4";
-
-    let _ = add_project(pool, project_id, project_logo).await?;
+    .fetch_optional(pool)
+    .await?
+    .map(|r| r.inserted);
 
-    Ok(())
+    Ok(UpsertOutcome::from_returned(inserted))
 }
 
-pub async fn list_projects(pool: &PgPool) -> anyhow::Result<()> {
-    let recs = sqlx::query!(
-        r#"
-        SELECT project_id, project_logo
-        FROM projects
-        ORDER BY project_id
-        "#
+// Upserts a crawled issue, creating its project (with the owner avatar as logo) the first time it is seen.
+pub async fn upsert_outer_issue(
+    pool: &PgPool,
+    issue: &OuterIssue,
+) -> anyhow::Result<UpsertOutcome> {
+    let issue_url = parse_github_url(&issue.url)
+        .filter(|url| url.issue_number().is_some())
+        .ok_or_else(|| anyhow::anyhow!("not an issue url: {}", issue.url))?;
+    let project_id = issue_url.project_id();
+
+    let known = sqlx::query!(
+        r#"SELECT EXISTS(SELECT 1 FROM projects WHERE project_id = $1) AS "exists!""#,
+        project_id
     )
-    .fetch_all(pool)
-    .await?;
+    .fetch_one(pool)
+    .await?
+    .exists;
 
-    for rec in recs {
-        println!("{}: {}", rec.project_id, rec.project_logo);
+    if !known {
+        let project_logo = get_project_logo(issue_url.owner, issue_url.repo).await?;
+        upsert_project(pool, &project_id, &project_logo).await?;
     }
 
-    Ok(())
+    let opened_at = DateTime::parse_from_rfc3339(&issue.created_at)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc));
+
+    upsert_issue(
+        pool,
+        &issue.url,
+        &project_id,
+        &issue.title,
+        &issue.body,
+        &issue.state,
+        issue.state_reason.as_deref(),
+        &issue.issue_labels,
+        &issue.author,
+        opened_at,
+    )
+    .await
 }
 
-pub async fn issue_exists(pool: &PgPool, issue_id: &str) -> anyhow::Result<bool> {
-    let exists = sqlx::query!(
+// `comments.issue_id` references `issues`, so upsert the issue first. Takes any executor so
+// the comment ingester can run it inside its own transaction.
+pub async fn upsert_comment<'e>(
+    executor: impl PgExecutor<'e>,
+    issue_id: &str,
+    comment: &IssueComment,
+) -> anyhow::Result<UpsertOutcome> {
+    let time = DateTime::parse_from_rfc3339(&comment.created_at)?.naive_utc();
+    let comment_updated_at = DateTime::parse_from_rfc3339(&comment.updated_at)?.with_timezone(&Utc);
+
+    let inserted = sqlx::query!(
         r#"
-        SELECT EXISTS(SELECT 1 FROM issues WHERE issue_id = $1) AS "exists!"
+        INSERT INTO comments (comment_id, issue_id, creator, time, content, comment_updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (comment_id) DO UPDATE
+        SET issue_id = EXCLUDED.issue_id,
+            creator = EXCLUDED.creator,
+            time = EXCLUDED.time,
+            content = EXCLUDED.content,
+            comment_updated_at = EXCLUDED.comment_updated_at,
+            updated_at = now()
+        WHERE (comments.issue_id, comments.creator, comments.time, comments.content, comments.comment_updated_at)
+            IS DISTINCT FROM
+            (EXCLUDED.issue_id, EXCLUDED.creator, EXCLUDED.time, EXCLUDED.content, EXCLUDED.comment_updated_at)
+        RETURNING (xmax = 0) AS "inserted!"
         "#,
-        issue_id
+        comment.url,
+        issue_id,
+        comment.author,
+        time,
+        comment.body,
+        comment_updated_at
     )
-    .fetch_one(pool)
+    .fetch_optional(executor)
     .await?
-    .exists;
+    .map(|r| r.inserted);
 
-    Ok(exists)
+    Ok(UpsertOutcome::from_returned(inserted))
 }
 
-pub async fn add_issue_with_check(
-    pool: &PgPool,
-    issue_id: &str,
-    title: &str,
-    description: &str,
-) -> anyhow::Result<()> {
-    // let issue_id = "https://github.com/jaykchen/issue-labeler/issues/24";
-
-    let project_id = issue_id.rsplitn(3, '/').nth(2).unwrap();
+pub async fn upsert_pull_request(pool: &PgPool, pull: &OuterPull) -> anyhow::Result<UpsertOutcome> {
+    let merged_at = pull
+        .merged_at
+        .as_deref()
+        .map(DateTime::parse_from_rfc3339)
+        .transpose()?
+        .map(|dt| dt.with_timezone(&Utc));
 
-    let owner_repo = project_id.rsplitn(3, '/').take(2).collect::<Vec<_>>();
-    let owner = owner_repo[1];
-    let repo = owner_repo[0];
-    if project_exists(pool, project_id).await? {
-    } else {
-        let project_logo: String = get_project_logo(owner, repo).await?;
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO pull_requests (pull_id, title, author, repository, merged_by, cross_referenced_issues,
+                                   merged_at, labels, review_decision, additions, deletions)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (pull_id) DO UPDATE
+        SET title = EXCLUDED.title,
+            author = EXCLUDED.author,
+            repository = EXCLUDED.repository,
+            merged_by = EXCLUDED.merged_by,
+            cross_referenced_issues = EXCLUDED.cross_referenced_issues,
+            merged_at = EXCLUDED.merged_at,
+            labels = EXCLUDED.labels,
+            review_decision = EXCLUDED.review_decision,
+            additions = EXCLUDED.additions,
+            deletions = EXCLUDED.deletions,
+            updated_at = now()
+        WHERE (pull_requests.title, pull_requests.author, pull_requests.repository, pull_requests.merged_by,
+               pull_requests.cross_referenced_issues, pull_requests.merged_at, pull_requests.labels,
+               pull_requests.review_decision, pull_requests.additions, pull_requests.deletions)
+            IS DISTINCT FROM
+            (EXCLUDED.title, EXCLUDED.author, EXCLUDED.repository, EXCLUDED.merged_by,
+             EXCLUDED.cross_referenced_issues, EXCLUDED.merged_at, EXCLUDED.labels,
+             EXCLUDED.review_decision, EXCLUDED.additions, EXCLUDED.deletions)
+        RETURNING (xmax = 0) AS "inserted!"
+        "#,
+        pull.url,
+        pull.title,
+        pull.author,
+        pull.repository,
+        pull.merged_by,
+        &pull.cross_referenced_issues,
+        merged_at,
+        &pull.labels,
+        pull.review_decision,
+        pull.additions,
+        pull.deletions,
+    )
+    .fetch_optional(pool)
+    .await?
+    .map(|r| r.inserted);
 
-        add_project(pool, project_id, &project_logo).await?;
-    }
+    Ok(UpsertOutcome::from_returned(inserted))
+}
 
-    if issue_exists(pool, issue_id).await? {
-    } else {
-        sqlx::query!(
-            r#"
-            INSERT INTO issues (issue_id, project_id, issue_title, issue_description)
-            VALUES ($1, $2, $3, $4)
-            "#,
-            issue_id,
-            project_id,
-            title,
-            description,
-        )
-        .execute(pool)
-        .await?;
+// Points the issues a PR closes back at it, open or merged, and recomputes their lifecycle:
+// the issue may have been crawled before the PR, e.g. closed by the merge and stored as
+// closed_unmerged. `cross_referenced_issues` is already stored by `upsert_pull_request`;
+// issues that were only mentioned keep their linked PR.
+pub async fn record_pull_request_links(pool: &PgPool, pull: &OuterPull) -> anyhow::Result<()> {
+    if pull.closing_issues.is_empty() {
+        return Ok(());
     }
-    Ok(())
-}
 
-pub async fn add_issue(
-    pool: &PgPool,
-    issue_id: &str,
-    project_id: &str,
-    title: &str,
-    description: &str,
-) -> anyhow::Result<()> {
     sqlx::query!(
         r#"
-        INSERT INTO issues (issue_id, project_id, issue_title, issue_description)
-        VALUES ($1, $2, $3, $4)
+        UPDATE issues
+        SET issue_linked_pr = $1, updated_at = now()
+        WHERE issue_id = ANY($2) AND issue_linked_pr IS DISTINCT FROM $1
         "#,
-        issue_id,
-        project_id,
-        title,
-        description,
+        pull.url,
+        &pull.closing_issues,
     )
     .execute(pool)
     .await?;
+
+    let at = pull.merged_at.as_deref().unwrap_or(&pull.updated_at);
+    let at = DateTime::parse_from_rfc3339(at)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc));
+    for issue_id in &pull.closing_issues {
+        refresh_issue_lifecycle(pool, issue_id, at).await?;
+    }
+
     Ok(())
 }
-pub async fn add_issue_test_1(pool: &PgPool) -> anyhow::Result<()> {
-    let issue_id = "https://github.com/jaykchen/issue-labeler/issues/24";
-    let project_id = "https://github.com/jaykchen/issue-labeler";
-    let title = "WASI-NN with GPU on Jetson Orin Nano";
-    let description = "demo";
 
-    sqlx::query!(
-        r#"
-        INSERT INTO issues (issue_id, project_id, issue_title, issue_description)
-        VALUES ($1, $2, $3, $4)
-        "#,
-        issue_id,
-        project_id,
-        title,
-        description,
-    )
-    .execute(pool)
-    .await?;
-    Ok(())
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Project {
+    pub project_id: String,
+    pub project_logo: String,
+    pub issues_list: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub primary_language: Option<String>,
+    pub topics: Vec<String>,
+    pub stars: Option<i32>,
+    pub forks: Option<i32>,
+    pub license: Option<String>,
+    pub default_branch: Option<String>,
+    pub open_issues: Option<i32>,
+    pub has_contributing: Option<bool>,
+    pub enriched_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
-pub async fn list_issues(pool: &PgPool, project_id: &str) -> anyhow::Result<()> {
-    let recs = sqlx::query!(
-        r#"
-        SELECT issue_id, issue_title, issue_description, issue_budget
-        FROM issues
-        WHERE project_id = $1
-        ORDER BY issue_id
-        "#,
-        project_id
-    )
-    .fetch_all(pool)
-    .await?;
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct TrackedIssue {
+    pub issue_id: String,
+    pub project_id: String,
+    pub issue_title: String,
+    pub issue_description: String,
+    pub issue_state: Option<String>,
+    pub issue_labels: Vec<String>,
+    pub issue_author: Option<String>,
+    pub issue_opened_at: Option<DateTime<Utc>>,
+    pub issue_budget: Option<i32>,
+    pub issue_budget_approved: Option<bool>,
+    pub issue_budget_reviewed_by: Option<String>,
+    pub issue_budget_reviewed_at: Option<DateTime<Utc>>,
+    pub issue_assignee: Option<String>,
+    pub issue_assignees: Vec<String>,
+    pub issue_assigned_at: Option<DateTime<Utc>>,
+    pub stale_flagged_at: Option<DateTime<Utc>>,
+    pub issue_linked_pr: Option<String>,
+    pub issue_status: Option<String>,
+    pub review_status: Option<ReviewStatus>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
 
-    for rec in recs {
-        println!(
-            "- [{}] {}: {} (${:?})",
-            rec.issue_id, rec.issue_title, rec.issue_description, rec.issue_budget
-        );
-    }
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Comment {
+    pub comment_id: String,
+    pub issue_id: String,
+    pub creator: String,
+    pub time: Option<NaiveDateTime>,
+    pub content: String,
+    pub comment_updated_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
 
-    Ok(())
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct PullRequest {
+    pub pull_id: String,
+    pub title: String,
+    pub author: String,
+    pub repository: String,
+    pub merged_by: String,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub cross_referenced_issues: Option<Vec<String>>,
+    pub labels: Vec<String>,
+    pub review_decision: Option<String>,
+    pub additions: i64,
+    pub deletions: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
-pub async fn get_issue(pool: &PgPool, issue_id: &str) -> anyhow::Result<()> {
-    let recs = sqlx::query!(
+pub async fn list_projects(pool: &PgPool) -> anyhow::Result<Vec<Project>> {
+    let projects = sqlx::query_as::<_, Project>(
         r#"
-        SELECT issue_id, issue_title, issue_description, issue_budget
-        FROM issues
-        WHERE issue_id = $1
-        ORDER BY issue_id
+        SELECT *
+        FROM projects
+        ORDER BY project_id
         "#,
-        issue_id
     )
     .fetch_all(pool)
     .await?;
 
-    for rec in recs {
-        println!(
-            "- [{}] {}: {} (${:?})",
-            rec.issue_id, rec.issue_title, rec.issue_description, rec.issue_budget
-        );
-    }
-
-    Ok(())
+    Ok(projects)
 }
 
-pub async fn add_comment(
-    pool: &PgPool,
-    comment_id: &str,
-    issue_id: &str,
-    creator: &str,
-    content: &str,
-) -> anyhow::Result<()> {
-    sqlx::query!(
+pub async fn get_project(pool: &PgPool, project_id: &str) -> anyhow::Result<Option<Project>> {
+    let project = sqlx::query_as::<_, Project>(
         r#"
-        INSERT INTO comments (comment_id, issue_id, creator, content)
-        VALUES ($1, $2, $3, $4)
+        SELECT *
+        FROM projects
+        WHERE project_id = $1
         "#,
-        comment_id,
-        issue_id,
-        creator,
-        content
     )
-    .execute(pool)
+    .bind(project_id)
+    .fetch_optional(pool)
     .await?;
-    Ok(())
+
+    Ok(project)
 }
-pub async fn add_comment_with_check(
-    pool: &PgPool,
-    comment_id: &str,
-    issue_id: &str,
-    creator: &str,
-    content: &str,
-) -> anyhow::Result<()> {
-    if issue_exists(pool, issue_id).await? {
-    } else {
-        let _ = add_issue_with_check(pool, issue_id, "title", "description").await?;
-    }
 
-    sqlx::query!(
+pub async fn list_issues(pool: &PgPool, project_id: &str) -> anyhow::Result<Vec<TrackedIssue>> {
+    let issues = sqlx::query_as::<_, TrackedIssue>(
         r#"
-            INSERT INTO comments (comment_id, issue_id, creator, content)
-            VALUES ($1, $2, $3, $4)
-            "#,
-        comment_id,
-        issue_id,
-        creator,
-        content
+        SELECT *
+        FROM issues
+        WHERE project_id = $1
+        ORDER BY issue_id
+        "#,
     )
-    .execute(pool)
+    .bind(project_id)
+    .fetch_all(pool)
     .await?;
-    Ok(())
+
+    Ok(issues)
 }
 
-pub async fn add_comment_test_1(pool: &PgPool) -> anyhow::Result<()> {
-    let comment_id = "https://github.com/jaykchen/issue-labeler/issues/24#issuecomment-1979927212";
-    let issue_id = "https://github.com/jaykchen/issue-labeler/issues/24";
-    let creator = "jaykchen";
-    let content = "This is a placeholder comment on this issue.";
-    sqlx::query!(
+pub async fn get_issue(pool: &PgPool, issue_id: &str) -> anyhow::Result<Option<TrackedIssue>> {
+    let issue = sqlx::query_as::<_, TrackedIssue>(
         r#"
-        INSERT INTO comments (comment_id, issue_id, creator, content)
-        VALUES ($1, $2, $3, $4)
+        SELECT *
+        FROM issues
+        WHERE issue_id = $1
         "#,
-        comment_id,
-        issue_id,
-        creator,
-        content
     )
-    .execute(pool)
+    .bind(issue_id)
+    .fetch_optional(pool)
     .await?;
-    Ok(())
+
+    Ok(issue)
 }
 
-pub async fn list_comments(pool: &PgPool, issue_id: &str) -> anyhow::Result<()> {
-    let recs = sqlx::query!(
+pub async fn list_comments(pool: &PgPool, issue_id: &str) -> anyhow::Result<Vec<Comment>> {
+    let comments = sqlx::query_as::<_, Comment>(
         r#"
-        SELECT comment_id, content
+        SELECT *
         FROM comments
         WHERE issue_id = $1
-        ORDER BY comment_id
+        ORDER BY time, comment_id
         "#,
-        issue_id
     )
+    .bind(issue_id)
     .fetch_all(pool)
     .await?;
 
-    for rec in recs {
-        println!("- [{}] {}", rec.comment_id, rec.content);
-    }
-
-    Ok(())
+    Ok(comments)
 }
 
-pub async fn list_pull_requests(
-    pool: &sqlx::PgPool,
-) -> anyhow::Result<Vec<(String, String, String, String, String, Vec<String>)>> {
-    let pull_requests = sqlx::query!(
+pub async fn list_pull_requests(pool: &PgPool) -> anyhow::Result<Vec<PullRequest>> {
+    let pull_requests = sqlx::query_as::<_, PullRequest>(
         r#"
-        SELECT pull_id, title, author, repository, merged_by, cross_referenced_issues
+        SELECT *
         FROM pull_requests
-        "#
+        ORDER BY repository, pull_id
+        "#,
     )
     .fetch_all(pool)
-    .await?
-    .iter()
-    .map(|r| {
-        (
-            r.pull_id.clone(),
-            r.title.clone(),
-            r.author.clone(),
-            r.repository.clone(),
-            r.merged_by.clone(),
-            r.cross_referenced_issues.clone().unwrap_or_default(), // Handle Option<Vec<String>>
-        )
-    })
-    .collect();
+    .await?;
 
     Ok(pull_requests)
 }
 
-pub async fn add_pull_request_with_check(
-    pool: &sqlx::PgPool,
-    pull_id: &str,
-    title: &str,
-    author: &str,
-    repository: &str,
-    merged_by: &str,
-    cross_referenced_issues: Vec<String>,
-) -> anyhow::Result<()> {
-    let exists = sqlx::query!(
+pub async fn get_pull_request(pool: &PgPool, pull_id: &str) -> anyhow::Result<Option<PullRequest>> {
+    let pull_request = sqlx::query_as::<_, PullRequest>(
         r#"
-        SELECT EXISTS(SELECT 1 FROM pull_requests WHERE pull_id = $1)
-        "#,
-        pull_id
-    )
-    .fetch_one(pool)
-    .await?
-    .exists
-    .unwrap_or(false);
-
-    if !exists {
-        add_pull_request(
-            pool,
-            pull_id,
-            title,
-            author,
-            repository,
-            merged_by,
-            cross_referenced_issues,
-        )
-        .await?;
-    }
-
-    Ok(())
-}
-pub async fn add_pull_request(
-    pool: &sqlx::PgPool,
-    pull_id: &str,
-    title: &str,
-    author: &str,
-    repository: &str,
-    merged_by: &str,
-    cross_referenced_issues: Vec<String>,
-) -> anyhow::Result<()> {
-    sqlx::query!(
-        r#"
-        INSERT INTO pull_requests (pull_id, title, author, repository, merged_by, cross_referenced_issues)
-        VALUES ($1, $2, $3, $4, $5, $6)
+        SELECT *
+        FROM pull_requests
+        WHERE pull_id = $1
         "#,
-        pull_id,
-        title,
-        author,
-        repository,
-        merged_by,
-        &cross_referenced_issues,
     )
-    .execute(pool)
+    .bind(pull_id)
+    .fetch_optional(pool)
     .await?;
 
-    Ok(())
+    Ok(pull_request)
 }
- */
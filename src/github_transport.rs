@@ -0,0 +1,257 @@
+use anyhow::anyhow;
+use http_req::{
+    request::{Method, Request},
+    uri::Uri,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const GRAPHQL_URL: &str = "https://api.github.com/graphql";
+
+// The only response headers the crawler looks at.
+const KEPT_HEADERS: [&str; 3] = ["x-ratelimit-remaining", "x-ratelimit-reset", "retry-after"];
+
+#[derive(Clone, Debug)]
+pub struct GitHubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl GitHubResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    }
+}
+
+fn kept_headers(headers: &http_req::response::Headers) -> Vec<(String, String)> {
+    KEPT_HEADERS
+        .iter()
+        .filter_map(|name| {
+            headers
+                .get(*name)
+                .map(|v| (name.to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+pub trait GitHubTransport {
+    fn post_gql(&self, payload: &Value) -> anyhow::Result<GitHubResponse>;
+    fn get(&self, url: &str) -> anyhow::Result<GitHubResponse>;
+}
+
+pub struct HttpTransport {
+    token: String,
+}
+
+impl HttpTransport {
+    pub fn new(token: &str) -> Self {
+        HttpTransport {
+            token: token.to_string(),
+        }
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        let token = std::env::var("GITHUB_TOKEN")
+            .map_err(|_| anyhow!("GITHUB_TOKEN is required to reach GitHub"))?;
+        Ok(Self::new(&token))
+    }
+}
+
+impl GitHubTransport for HttpTransport {
+    fn post_gql(&self, payload: &Value) -> anyhow::Result<GitHubResponse> {
+        let base_url = Uri::try_from(GRAPHQL_URL)?;
+        let body = payload.to_string().into_bytes();
+        let mut writer = Vec::new();
+
+        let res = Request::new(&base_url)
+            .method(Method::POST)
+            .header("User-Agent", "flows-network connector")
+            .header("Content-Type", "application/json")
+            .header("Authorization", &format!("Bearer {}", self.token))
+            .header("Content-Length", &body.len())
+            .body(&body)
+            .send(&mut writer)?;
+
+        Ok(GitHubResponse {
+            status: res.status_code().into(),
+            headers: kept_headers(res.headers()),
+            body: writer,
+        })
+    }
+
+    fn get(&self, url: &str) -> anyhow::Result<GitHubResponse> {
+        let url = Uri::try_from(url)?;
+        let mut writer = Vec::new();
+
+        let res = Request::new(&url)
+            .method(Method::GET)
+            .header("User-Agent", "flows-network connector")
+            .header("Content-Type", "application/json")
+            .header("Authorization", &format!("Bearer {}", self.token))
+            .header("CONNECTION", "close")
+            .send(&mut writer)?;
+
+        Ok(GitHubResponse {
+            status: res.status_code().into(),
+            headers: kept_headers(res.headers()),
+            body: writer,
+        })
+    }
+}
+
+// On-disk form of a recorded response. The status and kept headers are stored with the
+// body so rate-limited responses replay the same way as successful ones. A body that is
+// not JSON is stored as a JSON string.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl From<&GitHubResponse> for RecordedResponse {
+    fn from(res: &GitHubResponse) -> Self {
+        RecordedResponse {
+            status: res.status,
+            headers: res.headers.clone(),
+            body: serde_json::from_slice(&res.body)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&res.body).into_owned())),
+        }
+    }
+}
+
+impl From<RecordedResponse> for GitHubResponse {
+    fn from(recorded: RecordedResponse) -> Self {
+        let body = match recorded.body {
+            Value::String(text) => text.into_bytes(),
+            other => other.to_string().into_bytes(),
+        };
+        GitHubResponse {
+            status: recorded.status,
+            headers: recorded.headers,
+            body,
+        }
+    }
+}
+
+// Serves responses recorded on disk. Each request maps to `gql-<hash>.json` or
+// `get-<hash>.json` in `dir`, where the hash covers the GraphQL payload or the URL.
+pub struct FixtureTransport {
+    dir: PathBuf,
+}
+
+impl FixtureTransport {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        FixtureTransport {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn gql_fixture_path(&self, payload: &Value) -> PathBuf {
+        self.dir.join(fixture_name("gql", &payload.to_string()))
+    }
+
+    pub fn get_fixture_path(&self, url: &str) -> PathBuf {
+        self.dir.join(fixture_name("get", url))
+    }
+
+    fn replay(&self, path: PathBuf) -> anyhow::Result<GitHubResponse> {
+        let raw = std::fs::read(&path)
+            .map_err(|e| anyhow!("no recorded response at {}: {}", path.display(), e))?;
+        let recorded: RecordedResponse = serde_json::from_slice(&raw)
+            .map_err(|e| anyhow!("bad recorded response at {}: {}", path.display(), e))?;
+        Ok(recorded.into())
+    }
+}
+
+impl GitHubTransport for FixtureTransport {
+    fn post_gql(&self, payload: &Value) -> anyhow::Result<GitHubResponse> {
+        self.replay(self.gql_fixture_path(payload))
+    }
+
+    fn get(&self, url: &str) -> anyhow::Result<GitHubResponse> {
+        self.replay(self.get_fixture_path(url))
+    }
+}
+
+// Passes requests through to `inner` and saves every response, rate-limited ones included,
+// where a `FixtureTransport` over the same directory will look for it.
+pub struct RecordingTransport<T: GitHubTransport> {
+    inner: T,
+    fixtures: FixtureTransport,
+}
+
+impl<T: GitHubTransport> RecordingTransport<T> {
+    pub fn new(inner: T, dir: impl AsRef<Path>) -> Self {
+        RecordingTransport {
+            inner,
+            fixtures: FixtureTransport::new(dir),
+        }
+    }
+
+    fn save(&self, path: PathBuf, res: &GitHubResponse) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let recorded = RecordedResponse::from(res);
+        std::fs::write(&path, serde_json::to_vec_pretty(&recorded)?)?;
+        Ok(())
+    }
+}
+
+impl<T: GitHubTransport> GitHubTransport for RecordingTransport<T> {
+    fn post_gql(&self, payload: &Value) -> anyhow::Result<GitHubResponse> {
+        let res = self.inner.post_gql(payload)?;
+        self.save(self.fixtures.gql_fixture_path(payload), &res)?;
+        Ok(res)
+    }
+
+    fn get(&self, url: &str) -> anyhow::Result<GitHubResponse> {
+        let res = self.inner.get(url)?;
+        self.save(self.fixtures.get_fixture_path(url), &res)?;
+        Ok(res)
+    }
+}
+
+// FNV-1a, so fixture names stay stable across Rust releases unlike `DefaultHasher`.
+fn fixture_name(kind: &str, request: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in request.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{kind}-{hash:016x}.json")
+}
+
+thread_local! {
+    static TRANSPORT: RefCell<Option<Rc<dyn GitHubTransport>>> = RefCell::new(None);
+}
+
+pub fn set_github_transport(transport: impl GitHubTransport + 'static) {
+    TRANSPORT.with(|t| *t.borrow_mut() = Some(Rc::new(transport)));
+}
+
+// Falls back to `HttpTransport::from_env` the first time nothing has been set.
+pub fn github_transport() -> anyhow::Result<Rc<dyn GitHubTransport>> {
+    TRANSPORT.with(|t| {
+        let mut slot = t.borrow_mut();
+        if let Some(transport) = slot.as_ref() {
+            return Ok(transport.clone());
+        }
+        let transport: Rc<dyn GitHubTransport> = Rc::new(HttpTransport::from_env()?);
+        *slot = Some(transport.clone());
+        Ok(transport)
+    })
+}
@@ -0,0 +1,86 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Deserialize, Debug)]
+pub struct GraphQLResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(default)]
+    pub path: Vec<Value>,
+    #[serde(rename = "type")]
+    pub error_type: Option<String>,
+}
+
+impl GraphQLError {
+    // Errors without a path (syntax, validation) or on a root field mean the whole
+    // request failed; anything deeper only nulls out part of the tree.
+    pub fn is_fatal(&self) -> bool {
+        self.path.len() <= 1
+    }
+}
+
+impl fmt::Display for GraphQLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(error_type) = &self.error_type {
+            write!(f, "[{error_type}] ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if !self.path.is_empty() {
+            let path = self
+                .path
+                .iter()
+                .map(|p| match p {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+            write!(f, " at {path}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphQLErrors(pub Vec<GraphQLError>);
+
+impl fmt::Display for GraphQLErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "GraphQL response carried no data");
+        }
+        let messages = self.0.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        write!(f, "GraphQL errors: {}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for GraphQLErrors {}
+
+// Returns `data` when the request succeeded, even partially. Fails with `GraphQLErrors`
+// when there is no data or a root field errored, so a broken query never reads as empty.
+pub fn parse_graphql<T: DeserializeOwned>(body: &[u8]) -> anyhow::Result<T> {
+    let response: GraphQLResponse<T> = serde_json::from_slice(body)
+        .map_err(|e| anyhow::anyhow!("Failed to deserialize response: {}", e))?;
+
+    if response.errors.iter().any(GraphQLError::is_fatal) {
+        return Err(GraphQLErrors(response.errors).into());
+    }
+
+    match response.data {
+        Some(data) => {
+            for error in &response.errors {
+                log::warn!("partial GraphQL result: {error}");
+            }
+            Ok(data)
+        }
+        None => Err(GraphQLErrors(response.errors).into()),
+    }
+}
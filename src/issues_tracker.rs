@@ -1,141 +1,70 @@
-use chrono::{Datelike, NaiveDate, Timelike, Utc};
-use dotenv::dotenv;
-use flowsnet_platform_sdk::logger;
-use github_flows::{get_octo, GithubLogin};
-use octocrab_wasi::{models::issues::Issue, params::issues::Sort, params::Direction, Octocrab};
-use openai_flows::{
-    chat::{ChatModel, ChatOptions},
-    OpenAIFlows,
-};
-use serde_json::{json, to_string_pretty, Value};
-use std::env;
-
 use anyhow::anyhow;
 use chrono::Duration;
-use http_req::{
-    request::{Method, Request},
-    uri::Uri,
-};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use chrono::{Duration, NaiveDate, Utc};
-
-use anyhow::anyhow;
-use octocrab::{models::issues::Issue, Octocrab};
-use std::env;
-
-use http_req::{
-    request::{Method, Request},
-    uri::Uri,
-};
-use serde::{Deserialize, Serialize};
-use std::io::Write;
+use crate::comments::{fetch_issue_comments_from, IssueComment};
+use crate::date_windows::{DateWindow, DateWindowPlan};
+use crate::github_transport::github_transport;
+use crate::graphql::parse_graphql;
+use crate::rate_limit::{send_governed, with_governor};
+use crate::search_query::{DateField, SearchQuery};
+use std::collections::{BTreeMap, HashSet};
 
 pub fn inner_query_by_date_range(
-    start_date: &str,
-    n_days: i64,
-    issue_label: &str,
-    pr_label: &str,
-    is_issue: bool,
-    is_start: bool,
-) -> Vec<String> {
-    // let start_date ="2023-10-01";
-    // let issue_label = "hacktoberfest";
-    // let pr_label = "hacktoberfest-accepted";
-    let start_date =
-        NaiveDate::parse_from_str(start_date, "%Y-%m-%d").expect("Failed to parse date");
-
-    let date_point_vec = (0..20)
-        .map(|i| {
-            (start_date + Duration::days(n_days * i as i64))
-                .format("%Y-%m-%d")
-                .to_string()
-        })
-        .collect::<Vec<_>>();
-
-    let date_range_vec = date_point_vec
-        .windows(2)
-        .map(|x| x.join(".."))
-        .collect::<Vec<_>>();
-
-    let mut out = Vec::new();
-    for date_range in date_range_vec {
-        let query = if is_issue && is_start {
-            format!("label:{issue_label} is:issue is:open no:assignee created:{date_range} -label:spam -label:invalid")
-        } else if is_issue && !is_start {
-            format!("label:{issue_label} is:issue is:closed created:{date_range} -label:spam -label:invalid")
-        } else {
-            format!("label:{pr_label} is:pr is:merged created:{date_range} review:approved -label:spam -label:invalid")
-        };
-        out.push(query);
-    }
+    plan: &DateWindowPlan,
+    base: &SearchQuery,
+) -> anyhow::Result<Vec<String>> {
+    // let plan = DateWindowPlan::new("2023-10-01", Some("2023-11-01"), Stride::Days(2))?;
+    // let base = SearchQuery::open_unassigned_issues("hacktoberfest");
+    let out = plan
+        .windows()?
+        .iter()
+        .map(|window| base.clone().created(window.to_search_range()).to_string())
+        .collect();
 
-    out
+    Ok(out)
 }
 
-pub async fn github_http_post_gql(query: &str) -> anyhow::Result<Vec<u8>> {
-    let token = env::var("GITHUB_TOKEN").expect("github_token is required");
-    let base_url = "https://api.github.com/graphql";
-    let base_url = Uri::try_from(base_url).unwrap();
-    let mut writer = Vec::new();
-
-    let query = serde_json::json!({"query": query});
-    match Request::new(&base_url)
-        .method(Method::POST)
-        .header("User-Agent", "flows-network connector")
-        .header("Content-Type", "application/json")
-        .header("Authorization", &format!("Bearer {}", token))
-        .header("Content-Length", &query.to_string().len())
-        .body(&query.to_string().into_bytes())
-        .send(&mut writer)
-    {
+pub async fn github_http_post_gql(query: &str, variables: Value) -> anyhow::Result<Vec<u8>> {
+    let payload = serde_json::json!({"query": query, "variables": variables});
+    let transport = github_transport()?;
+
+    match send_governed(|| transport.post_gql(&payload)).await {
         Ok(res) => {
-            if !res.status_code().is_success() {
-                log::error!("Github http error {:?}", res.status_code());
-                return Err(anyhow::anyhow!("Github http error {:?}", res.status_code()));
+            with_governor(|g| g.observe_graphql(&res.body));
+            if !res.is_success() {
+                log::error!("Github http error {:?}", res.status);
+                return Err(anyhow::anyhow!("Github http error {:?}", res.status));
             }
-            Ok(writer)
+            Ok(res.body)
         }
         Err(_e) => {
             log::error!("Error getting response from Github: {:?}", _e);
-            Err(anyhow::anyhow!(_e))
+            Err(_e)
         }
     }
 }
 
 pub async fn github_http_get(url: &str) -> anyhow::Result<Vec<u8>> {
-    let token = std::env::var("GITHUB_TOKEN").expect("github_token is required");
-    let mut writer = Vec::new();
-    let url = Uri::try_from(url).unwrap();
-
-    match Request::new(&url)
-        .method(Method::GET)
-        .header("User-Agent", "flows-network connector")
-        .header("Content-Type", "application/json")
-        .header("Authorization", &format!("Bearer {}", token))
-        .header("CONNECTION", "close")
-        .send(&mut writer)
-    {
+    let transport = github_transport()?;
+
+    match send_governed(|| transport.get(url)).await {
         Ok(res) => {
-            if !res.status_code().is_success() {
-                println!("Github http error {:?}", res.status_code());
-                return Err(anyhow::anyhow!("Github http error {:?}", res.status_code()));
+            if !res.is_success() {
+                log::error!("Github http error {:?}", res.status);
+                return Err(anyhow::anyhow!("Github http error {:?}", res.status));
             }
-            Ok(writer)
+            Ok(res.body)
         }
         Err(_e) => {
-            println!("Error getting response from Github: {:?}", _e);
-            Err(anyhow::anyhow!(_e))
+            log::error!("Error getting response from Github: {:?}", _e);
+            Err(_e)
         }
     }
 }
 
 pub async fn get_project_logo(owner: &str, repo: &str) -> anyhow::Result<String> {
-    #[derive(Serialize, Deserialize)]
-    struct GraphQLResponse {
-        data: RepositoryData,
-    }
-
     #[derive(Serialize, Deserialize)]
     struct RepositoryData {
         repository: OwnerData,
@@ -152,49 +81,96 @@ pub async fn get_project_logo(owner: &str, repo: &str) -> anyhow::Result<String>
         avatarUrl: String,
     }
 
-    let query_str = format!(
-        r#"
-        query {{
-            repository(owner: "{owner}", name: "{repo}") {{
-                owner {{
+    let query_str = r#"
+        query ($owner: String!, $repo: String!) {
+            repository(owner: $owner, name: $repo) {
+                owner {
                     login
-                    ... on User {{
+                    ... on User {
                         avatarUrl
-                    }}
-                    ... on Organization {{
+                    }
+                    ... on Organization {
                         avatarUrl
-                    }}
-                }}
-            }}
-        }}
-        "#,
-    );
+                    }
+                }
+            }
+        }
+        "#;
 
-    let response = github_http_post_gql(&query_str).await?;
+    let variables = serde_json::json!({ "owner": owner, "repo": repo });
+    let response = github_http_post_gql(query_str, variables).await?;
 
-    let parsed_response: GraphQLResponse = serde_json::from_slice(&response)?;
-    let owner_info = parsed_response.data.repository.owner;
+    let parsed_response: RepositoryData = parse_graphql(&response)?;
+    let owner_info = parsed_response.repository.owner;
     Ok(owner_info.avatarUrl)
 }
 
+pub const SEARCH_RESULT_CAP: i64 = 1000;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct OuterIssue {
     pub title: String,
     pub url: String,
     pub author: String,
     pub body: String,
+    pub state: String,
+    pub state_reason: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub closed_at: Option<String>,
     pub repository: String,
     pub repository_stars: i64,
     pub issue_labels: Vec<String>,
-    pub comments: Vec<String>,
+    pub comments: Vec<IssueComment>,
+    // False when some comment could not be read, so `comments` must not be used to prune
+    // the stored ones.
+    #[serde(default)]
+    pub comments_complete: bool,
+    pub assignees: Vec<String>,
+    pub assignment_events: Vec<AssignmentEvent>,
 }
 
-pub async fn search_issues_open(query: &str) -> anyhow::Result<Vec<OuterIssue>> {
-    #[derive(Serialize, Deserialize, Clone, Debug)]
-    struct GraphQLResponse {
-        data: Option<Data>,
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssignmentAction {
+    Assigned,
+    Unassigned,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssignmentEvent {
+    pub action: AssignmentAction,
+    pub assignee: String,
+    pub actor: String,
+    pub created_at: String,
+}
+
+// Page sizes for the connections nested in each search hit. Labels and comments beyond the
+// first page are fetched with follow-up queries, timeline items keep only the latest ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NestedPageSizes {
+    pub labels: i32,
+    pub comments: i32,
+    pub timeline_items: i32,
+}
+
+impl Default for NestedPageSizes {
+    fn default() -> Self {
+        NestedPageSizes {
+            labels: 10,
+            comments: 10,
+            timeline_items: 20,
+        }
     }
+}
 
+pub async fn search_issues_open(query: &str) -> anyhow::Result<Vec<OuterIssue>> {
+    search_issues_open_with(query, NestedPageSizes::default()).await
+}
+
+pub async fn search_issues_open_with(
+    query: &str,
+    page_sizes: NestedPageSizes,
+) -> anyhow::Result<Vec<OuterIssue>> {
     #[derive(Serialize, Deserialize, Clone, Debug)]
     struct Data {
         search: Option<Search>,
@@ -220,13 +196,21 @@ pub async fn search_issues_open(query: &str) -> anyhow::Result<Vec<OuterIssue>>
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
     struct Issue {
+        id: Option<String>,
         title: Option<String>,
         url: Option<String>,
         body: Option<String>,
+        state: Option<String>,
+        stateReason: Option<String>,
+        createdAt: Option<String>,
+        updatedAt: Option<String>,
+        closedAt: Option<String>,
         author: Option<Author>,
         repository: Option<Repository>,
         labels: Option<Labels>,
         comments: Option<Comments>,
+        assignees: Option<Assignees>,
+        timelineItems: Option<TimelineItems>,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -248,6 +232,7 @@ pub async fn search_issues_open(query: &str) -> anyhow::Result<Vec<OuterIssue>>
     #[derive(Serialize, Deserialize, Clone, Debug)]
     struct Labels {
         edges: Option<Vec<LabelEdge>>,
+        pageInfo: Option<PageInfo>,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -263,6 +248,7 @@ pub async fn search_issues_open(query: &str) -> anyhow::Result<Vec<OuterIssue>>
     #[derive(Serialize, Deserialize, Clone, Debug)]
     struct Comments {
         edges: Option<Vec<CommentEdge>>,
+        pageInfo: Option<PageInfo>,
     }
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -272,147 +258,1175 @@ pub async fn search_issues_open(query: &str) -> anyhow::Result<Vec<OuterIssue>>
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
     struct Comment {
+        url: Option<String>,
         author: Option<Author>,
         body: Option<String>,
+        createdAt: Option<String>,
+        updatedAt: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Assignees {
+        nodes: Option<Vec<Author>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct TimelineItems {
+        nodes: Option<Vec<TimelineItem>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct TimelineItem {
+        __typename: Option<String>,
+        createdAt: Option<String>,
+        actor: Option<Author>,
+        assignee: Option<Author>,
+    }
+
+    fn next_cursor(page_info: &Option<PageInfo>) -> Option<String> {
+        page_info
+            .as_ref()
+            .filter(|p| p.hasNextPage)
+            .and_then(|p| p.endCursor.clone())
     }
 
-    let first_comments = 10;
-    let first_timeline_items = 10;
     let mut all_issues = Vec::new();
     let mut after_cursor: Option<String> = None;
-    let file_path = "issues.txt";
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(file_path)?;
-    let mut count = 0;
 
     for _ in 0..10 {
-        let query_str = format!(
-            r#"
-            query {{
-                search(query: "{}", type: ISSUE, first: 100, after: {}) {{
+        let query_str = r#"
+            query ($searchQuery: String!, $after: String, $firstLabels: Int!, $firstComments: Int!, $lastTimelineItems: Int!) {
+                search(query: $searchQuery, type: ISSUE, first: 100, after: $after) {
                     issueCount
-                    edges {{
-                        node {{
-                            ... on Issue {{
+                    edges {
+                        node {
+                            ... on Issue {
+                                id
                                 title
                                 url
                                 body
-                                author {{
+                                state
+                                stateReason
+                                createdAt
+                                updatedAt
+                                closedAt
+                                author {
                                     login
-                                }}
-                                repository {{
+                                }
+                                repository {
                                     url
-                                    stargazers {{
+                                    stargazers {
                                         totalCount
-                                    }}
-                                }}
-                                labels(first: 10) {{
-                                    edges {{
-                                        node {{
+                                    }
+                                }
+                                labels(first: $firstLabels) {
+                                    edges {
+                                        node {
                                             name
-                                        }}
-                                    }}
-                                }}
-                                comments(first: 10) {{
-                                    edges {{
-                                        node {{
-                                            author {{
+                                        }
+                                    }
+                                    pageInfo {
+                                        endCursor
+                                        hasNextPage
+                                    }
+                                }
+                                comments(first: $firstComments) {
+                                    edges {
+                                        node {
+                                            url
+                                            author {
                                                 login
-                                            }}
+                                            }
                                             body
-                                        }}
-                                    }}
-                                }}
-                            }}
-                        }}
-                    }}
-                    pageInfo {{
+                                            createdAt
+                                            updatedAt
+                                        }
+                                    }
+                                    pageInfo {
+                                        endCursor
+                                        hasNextPage
+                                    }
+                                }
+                                assignees(first: 10) {
+                                    nodes {
+                                        login
+                                    }
+                                }
+                                timelineItems(itemTypes: [ASSIGNED_EVENT, UNASSIGNED_EVENT], last: $lastTimelineItems) {
+                                    nodes {
+                                        __typename
+                                        ... on AssignedEvent {
+                                            createdAt
+                                            actor {
+                                                login
+                                            }
+                                            assignee {
+                                                ... on User {
+                                                    login
+                                                }
+                                            }
+                                        }
+                                        ... on UnassignedEvent {
+                                            createdAt
+                                            actor {
+                                                login
+                                            }
+                                            assignee {
+                                                ... on User {
+                                                    login
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    pageInfo {
                         endCursor
                         hasNextPage
-                    }}
-                }}
-            }}
-            "#,
-            query.replace("\"", "\\\""),
-            after_cursor
-                .as_ref()
-                .map_or(String::from("null"), |c| format!("\"{}\"", c)),
-        );
+                    }
+                }
+                rateLimit {
+                    cost
+                    remaining
+                    resetAt
+                }
+            }
+            "#;
 
-        let response_body = github_http_post_gql(&query_str)
+        let variables = serde_json::json!({
+            "searchQuery": query,
+            "after": after_cursor,
+            "firstLabels": page_sizes.labels,
+            "firstComments": page_sizes.comments,
+            "lastTimelineItems": page_sizes.timeline_items,
+        });
+        let response_body = github_http_post_gql(query_str, variables)
             .await
             .map_err(|e| anyhow!("Failed to post GraphQL query: {}", e))?;
 
-        let response: GraphQLResponse = serde_json::from_slice(&response_body)
-            .map_err(|e| anyhow!("Failed to deserialize response: {}", e))?;
-
-        if let Some(data) = response.data {
-            if let Some(search) = data.search {
-                for edge in search.edges.unwrap_or_default() {
-                    if let Some(issue) = edge.node {
-                        let labels = issue.labels.map_or(Vec::new(), |labels| {
-                            labels.edges.map_or(Vec::new(), |edges| {
-                                edges
-                                    .iter()
-                                    .filter_map(|edge| {
-                                        edge.node
-                                            .as_ref()
-                                            .map(|label| label.name.clone().unwrap_or_default())
-                                    })
-                                    .collect()
+        let data: Data = parse_graphql(&response_body)?;
+
+        if let Some(search) = data.search {
+            let issue_count = search.issueCount.unwrap_or(0) as i64;
+            if after_cursor.is_none() && issue_count > SEARCH_RESULT_CAP {
+                log::warn!(
+                    "query matches {issue_count} issues, only the first {SEARCH_RESULT_CAP} are reachable: {query}"
+                );
+            }
+            for edge in search.edges.unwrap_or_default() {
+                if let Some(issue) = edge.node {
+                    let url = issue.url.unwrap_or_default();
+                    let labels_next = issue.labels.as_ref().and_then(|l| next_cursor(&l.pageInfo));
+                    let comments_next = issue
+                        .comments
+                        .as_ref()
+                        .and_then(|c| next_cursor(&c.pageInfo));
+
+                    let mut labels: Vec<String> = issue.labels.map_or(Vec::new(), |labels| {
+                        labels.edges.map_or(Vec::new(), |edges| {
+                            edges
+                                .iter()
+                                .filter_map(|edge| {
+                                    edge.node
+                                        .as_ref()
+                                        .map(|label| label.name.clone().unwrap_or_default())
+                                })
+                                .collect()
+                        })
+                    });
+                    // A null page info hides whether more comments follow.
+                    let comments_paged = issue
+                        .comments
+                        .as_ref()
+                        .is_some_and(|c| c.pageInfo.is_some());
+                    let comment_edges = issue.comments.and_then(|c| c.edges);
+                    let fetched_comments = comment_edges.as_ref().map(Vec::len);
+                    let mut comments: Vec<IssueComment> = comment_edges
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|edge| {
+                            let comment = edge.node?;
+                            Some(IssueComment {
+                                url: comment.url?,
+                                // Comments from deleted accounts have no author.
+                                author: comment
+                                    .author
+                                    .and_then(|a| a.login)
+                                    .unwrap_or_else(|| "ghost".to_string()),
+                                body: comment.body.unwrap_or_default(),
+                                created_at: comment.createdAt?,
+                                updated_at: comment.updatedAt?,
                             })
-                        });
-                        let temp_str = String::from("");
-                        let comments = issue.comments.map_or(Vec::new(), |comments| {
-                            comments.edges.map_or(Vec::new(), |edges| {
-                                edges
-                                    .iter()
-                                    .filter_map(|edge| {
-                                        edge.node.as_ref().map(|comment| {
-                                            format!(
-                                                "{}: {}",
-                                                comment.author.as_ref().map_or("", |a| a
-                                                    .login
-                                                    .as_ref()
-                                                    .unwrap_or(&temp_str)),
-                                                comment.body.as_ref().unwrap_or(&"".to_string())
-                                            )
-                                        })
-                                    })
-                                    .collect()
+                        })
+                        .collect();
+
+                    if let (Some(after), Some(node_id)) = (labels_next, issue.id.as_deref()) {
+                        labels.extend(fetch_labels_from(node_id, Some(after)).await?);
+                    }
+                    let mut comments_complete =
+                        comments_paged && fetched_comments == Some(comments.len());
+                    if let Some(after) = comments_next {
+                        let rest = fetch_issue_comments_from(&url, Some(after)).await?;
+                        comments.extend(rest.comments);
+                        comments_complete &= rest.complete;
+                    }
+
+                    let assignees = issue
+                        .assignees
+                        .and_then(|a| a.nodes)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|a| a.login)
+                        .collect();
+                    let assignment_events = issue
+                        .timelineItems
+                        .and_then(|t| t.nodes)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|item| {
+                            let action = match item.__typename.as_deref() {
+                                Some("AssignedEvent") => AssignmentAction::Assigned,
+                                Some("UnassignedEvent") => AssignmentAction::Unassigned,
+                                _ => return None,
+                            };
+                            Some(AssignmentEvent {
+                                action,
+                                assignee: item.assignee.and_then(|a| a.login)?,
+                                actor: item.actor.and_then(|a| a.login).unwrap_or_default(),
+                                created_at: item.createdAt?,
                             })
-                        });
-
-                        all_issues.push(OuterIssue {
-                            title: issue.title.unwrap_or_default(),
-                            url: issue.url.unwrap_or_default(),
-                            author: issue
-                                .author
-                                .map_or(String::new(), |author| author.login.unwrap_or_default()),
-                            body: issue.body.unwrap_or_default(),
-                            repository: issue
-                                .repository
-                                .clone() // Clone here
-                                .map_or(String::new(), |repo| repo.url.unwrap_or_default()),
-                            repository_stars: issue.repository.map_or(0, |repo| {
-                                repo.stargazers
-                                    .map_or(0, |stars| stars.totalCount.unwrap_or(0))
-                            }),
-                            issue_labels: labels,
-                            comments: comments,
-                        });
+                        })
+                        .collect();
+
+                    all_issues.push(OuterIssue {
+                        title: issue.title.unwrap_or_default(),
+                        url,
+                        author: issue
+                            .author
+                            .map_or(String::new(), |author| author.login.unwrap_or_default()),
+                        body: issue.body.unwrap_or_default(),
+                        state: issue.state.unwrap_or_default(),
+                        state_reason: issue.stateReason,
+                        created_at: issue.createdAt.unwrap_or_default(),
+                        updated_at: issue.updatedAt.unwrap_or_default(),
+                        closed_at: issue.closedAt,
+                        repository: issue
+                            .repository
+                            .clone() // Clone here
+                            .map_or(String::new(), |repo| repo.url.unwrap_or_default()),
+                        repository_stars: issue.repository.map_or(0, |repo| {
+                            repo.stargazers
+                                .map_or(0, |stars| stars.totalCount.unwrap_or(0))
+                        }),
+                        issue_labels: labels,
+                        comments,
+                        comments_complete,
+                        assignees,
+                        assignment_events,
+                    });
+                }
+            }
+            if search.pageInfo.hasNextPage {
+                after_cursor = search.pageInfo.endCursor
+            } else {
+                break;
+            }
+        }
+    }
+
+    Ok(all_issues)
+}
+
+// Labels on the issue or pull request with GraphQL node id `node_id`, starting after `after`.
+pub async fn fetch_labels_from(
+    node_id: &str,
+    after: Option<String>,
+) -> anyhow::Result<Vec<String>> {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Data {
+        node: Option<Node>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Node {
+        labels: Option<Labels>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Labels {
+        nodes: Option<Vec<Label>>,
+        pageInfo: PageInfo,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct PageInfo {
+        endCursor: Option<String>,
+        hasNextPage: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Label {
+        name: Option<String>,
+    }
+
+    let query_str = r#"
+        query ($id: ID!, $after: String) {
+            node(id: $id) {
+                ... on Labelable {
+                    labels(first: 100, after: $after) {
+                        nodes {
+                            name
+                        }
+                        pageInfo {
+                            endCursor
+                            hasNextPage
+                        }
+                    }
+                }
+            }
+            rateLimit {
+                cost
+                remaining
+                resetAt
+            }
+        }
+        "#;
+
+    let mut all_labels = Vec::new();
+    let mut after_cursor = after;
+
+    loop {
+        let variables = serde_json::json!({ "id": node_id, "after": after_cursor });
+        let response_body = github_http_post_gql(query_str, variables)
+            .await
+            .map_err(|e| anyhow!("Failed to post GraphQL query: {}", e))?;
+
+        let data: Data = parse_graphql(&response_body)?;
+        let Some(labels) = data.node.and_then(|n| n.labels) else {
+            break;
+        };
+
+        all_labels.extend(
+            labels
+                .nodes
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|l| l.name),
+        );
+        match labels.pageInfo.endCursor {
+            Some(cursor) if labels.pageInfo.hasNextPage => after_cursor = Some(cursor),
+            _ => break,
+        }
+    }
+
+    Ok(all_labels)
+}
+
+// Issues the pull request with node id `node_id` closes, starting after `after`.
+pub async fn fetch_closing_issues_from(
+    node_id: &str,
+    after: Option<String>,
+) -> anyhow::Result<Vec<String>> {
+    let query_str = r#"
+        query ($id: ID!, $after: String) {
+            node(id: $id) {
+                ... on PullRequest {
+                    items: closingIssuesReferences(first: 100, after: $after) {
+                        nodes {
+                            url
+                        }
+                        pageInfo {
+                            endCursor
+                            hasNextPage
+                        }
                     }
                 }
-                if search.pageInfo.hasNextPage {
-                    after_cursor = search.pageInfo.endCursor
-                } else {
-                    break;
+            }
+            rateLimit {
+                cost
+                remaining
+                resetAt
+            }
+        }
+        "#;
+
+    fetch_pull_issue_refs(query_str, node_id, after).await
+}
+
+// Issues that cross-reference the pull request with node id `node_id`, starting after `after`.
+pub async fn fetch_cross_references_from(
+    node_id: &str,
+    after: Option<String>,
+) -> anyhow::Result<Vec<String>> {
+    let query_str = r#"
+        query ($id: ID!, $after: String) {
+            node(id: $id) {
+                ... on PullRequest {
+                    items: timelineItems(itemTypes: [CROSS_REFERENCED_EVENT], first: 100, after: $after) {
+                        nodes {
+                            ... on CrossReferencedEvent {
+                                source {
+                                    ... on Issue {
+                                        url
+                                    }
+                                }
+                            }
+                        }
+                        pageInfo {
+                            endCursor
+                            hasNextPage
+                        }
+                    }
                 }
             }
+            rateLimit {
+                cost
+                remaining
+                resetAt
+            }
+        }
+        "#;
+
+    fetch_pull_issue_refs(query_str, node_id, after).await
+}
+
+// Pages through the `items` connection of `query_str`, whose nodes are either issues or
+// events with an issue `source`.
+async fn fetch_pull_issue_refs(
+    query_str: &str,
+    node_id: &str,
+    after: Option<String>,
+) -> anyhow::Result<Vec<String>> {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Data {
+        node: Option<Node>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Node {
+        items: Option<Items>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Items {
+        nodes: Option<Vec<Item>>,
+        pageInfo: PageInfo,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct PageInfo {
+        endCursor: Option<String>,
+        hasNextPage: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Item {
+        url: Option<String>,
+        source: Option<IssueRef>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct IssueRef {
+        url: Option<String>,
+    }
+
+    let mut all_urls = Vec::new();
+    let mut after_cursor = after;
+
+    loop {
+        let variables = serde_json::json!({ "id": node_id, "after": after_cursor });
+        let response_body = github_http_post_gql(query_str, variables)
+            .await
+            .map_err(|e| anyhow!("Failed to post GraphQL query: {}", e))?;
+
+        let data: Data = parse_graphql(&response_body)?;
+        let Some(items) = data.node.and_then(|n| n.items) else {
+            break;
+        };
+
+        all_urls.extend(
+            items
+                .nodes
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|i| i.url.or_else(|| i.source.and_then(|s| s.url))),
+        );
+        match items.pageInfo.endCursor {
+            Some(cursor) if items.pageInfo.hasNextPage => after_cursor = Some(cursor),
+            _ => break,
+        }
+    }
+
+    Ok(all_urls)
+}
+
+pub async fn search_result_count(query: &str) -> anyhow::Result<i64> {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Data {
+        search: Option<Search>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Search {
+        issueCount: Option<i64>,
+    }
+
+    let query_str = r#"
+        query ($searchQuery: String!) {
+            search(query: $searchQuery, type: ISSUE, first: 1) {
+                issueCount
+            }
+            rateLimit {
+                cost
+                remaining
+                resetAt
+            }
+        }
+        "#;
+
+    let variables = serde_json::json!({ "searchQuery": query });
+    let response_body = github_http_post_gql(query_str, variables)
+        .await
+        .map_err(|e| anyhow!("Failed to post GraphQL query: {}", e))?;
+
+    let data: Data = parse_graphql(&response_body)?;
+
+    Ok(data
+        .search
+        .and_then(|search| search.issueCount)
+        .unwrap_or(0))
+}
+
+// Halves `window` until every piece matches at most SEARCH_RESULT_CAP items for `base`.
+// A window that can no longer be split (under two seconds) is kept and logged.
+pub async fn split_window_under_cap(
+    base: &SearchQuery,
+    field: DateField,
+    window: DateWindow,
+) -> anyhow::Result<Vec<DateWindow>> {
+    let mut fitting = Vec::new();
+    let mut pending = vec![window];
+
+    while let Some(window) = pending.pop() {
+        let query = base
+            .clone()
+            .date(field, window.to_search_range())
+            .to_string();
+        let count = search_result_count(&query).await?;
+
+        if count <= SEARCH_RESULT_CAP {
+            fitting.push(window);
+            continue;
+        }
+
+        // Search ranges are rendered to the second, so split on whole seconds.
+        let half = Duration::seconds(window.duration().num_seconds() / 2);
+        if half < Duration::seconds(1) {
+            log::warn!("window {window:?} still matches {count} items and cannot be split further");
+            fitting.push(window);
+            continue;
+        }
+
+        let mid = window.start + half;
+        log::info!("window {window:?} matches {count} items, splitting at {mid}");
+        pending.push(DateWindow::new(mid, window.end)?);
+        pending.push(DateWindow::new(window.start, mid)?);
+    }
+
+    Ok(fitting)
+}
+
+pub async fn search_issues_in_window(
+    base: &SearchQuery,
+    field: DateField,
+    window: DateWindow,
+) -> anyhow::Result<Vec<OuterIssue>> {
+    let mut seen = HashSet::new();
+    let mut all_issues = Vec::new();
+
+    for sub_window in split_window_under_cap(base, field, window).await? {
+        let query = base
+            .clone()
+            .date(field, sub_window.to_search_range())
+            .to_string();
+        for issue in search_issues_open(&query).await? {
+            if seen.insert(issue.url.clone()) {
+                all_issues.push(issue);
+            }
         }
     }
 
     Ok(all_issues)
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OuterPull {
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    pub repository: String,
+    pub merged_by: String,
+    pub merged_at: Option<String>,
+    pub updated_at: String,
+    pub labels: Vec<String>,
+    pub review_decision: Option<String>,
+    pub additions: i64,
+    pub deletions: i64,
+    // Issues the PR declares it closes (e.g. "Fixes #12"), a subset of `cross_referenced_issues`.
+    pub closing_issues: Vec<String>,
+    pub cross_referenced_issues: Vec<String>,
+}
+
+pub async fn search_pull_requests(query: &str) -> anyhow::Result<Vec<OuterPull>> {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Data {
+        search: Option<Search>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Search {
+        issueCount: Option<i32>,
+        edges: Option<Vec<Edge>>,
+        pageInfo: PageInfo,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct PageInfo {
+        endCursor: Option<String>,
+        hasNextPage: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Edge {
+        node: Option<PullRequest>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct PullRequest {
+        id: Option<String>,
+        title: Option<String>,
+        url: Option<String>,
+        author: Option<Author>,
+        repository: Option<Repository>,
+        mergedBy: Option<Author>,
+        mergedAt: Option<String>,
+        updatedAt: Option<String>,
+        labels: Option<Labels>,
+        reviewDecision: Option<String>,
+        additions: Option<i64>,
+        deletions: Option<i64>,
+        closingIssuesReferences: Option<ClosingIssues>,
+        timelineItems: Option<TimelineItems>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Author {
+        login: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Repository {
+        url: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Labels {
+        nodes: Option<Vec<Label>>,
+        pageInfo: Option<PageInfo>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Label {
+        name: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct ClosingIssues {
+        nodes: Option<Vec<IssueRef>>,
+        pageInfo: Option<PageInfo>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct IssueRef {
+        url: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct TimelineItems {
+        nodes: Option<Vec<CrossReference>>,
+        pageInfo: Option<PageInfo>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct CrossReference {
+        source: Option<IssueRef>,
+    }
+
+    fn next_cursor(page_info: &Option<PageInfo>) -> Option<String> {
+        page_info
+            .as_ref()
+            .filter(|p| p.hasNextPage)
+            .and_then(|p| p.endCursor.clone())
+    }
+
+    let mut all_pulls = Vec::new();
+    let mut after_cursor: Option<String> = None;
+
+    for _ in 0..10 {
+        let query_str = r#"
+            query ($searchQuery: String!, $after: String) {
+                search(query: $searchQuery, type: ISSUE, first: 100, after: $after) {
+                    issueCount
+                    edges {
+                        node {
+                            ... on PullRequest {
+                                id
+                                title
+                                url
+                                author {
+                                    login
+                                }
+                                repository {
+                                    url
+                                }
+                                mergedBy {
+                                    login
+                                }
+                                mergedAt
+                                updatedAt
+                                labels(first: 10) {
+                                    nodes {
+                                        name
+                                    }
+                                    pageInfo {
+                                        endCursor
+                                        hasNextPage
+                                    }
+                                }
+                                reviewDecision
+                                additions
+                                deletions
+                                closingIssuesReferences(first: 10) {
+                                    nodes {
+                                        url
+                                    }
+                                    pageInfo {
+                                        endCursor
+                                        hasNextPage
+                                    }
+                                }
+                                timelineItems(itemTypes: [CROSS_REFERENCED_EVENT], first: 20) {
+                                    nodes {
+                                        ... on CrossReferencedEvent {
+                                            source {
+                                                ... on Issue {
+                                                    url
+                                                }
+                                            }
+                                        }
+                                    }
+                                    pageInfo {
+                                        endCursor
+                                        hasNextPage
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    pageInfo {
+                        endCursor
+                        hasNextPage
+                    }
+                }
+                rateLimit {
+                    cost
+                    remaining
+                    resetAt
+                }
+            }
+            "#;
+
+        let variables = serde_json::json!({ "searchQuery": query, "after": after_cursor });
+        let response_body = github_http_post_gql(query_str, variables)
+            .await
+            .map_err(|e| anyhow!("Failed to post GraphQL query: {}", e))?;
+
+        let data: Data = parse_graphql(&response_body)?;
+
+        let Some(search) = data.search else {
+            break;
+        };
+
+        let pull_count = search.issueCount.unwrap_or(0) as i64;
+        if after_cursor.is_none() && pull_count > SEARCH_RESULT_CAP {
+            log::warn!(
+                "query matches {pull_count} pull requests, only the first {SEARCH_RESULT_CAP} are reachable: {query}"
+            );
+        }
+
+        for edge in search.edges.unwrap_or_default() {
+            let Some(pull) = edge.node else {
+                continue;
+            };
+            // Non-PR hits deserialize as an empty node.
+            let Some(url) = pull.url else {
+                continue;
+            };
+
+            let login = |author: Option<Author>| author.and_then(|a| a.login).unwrap_or_default();
+            let labels_next = pull.labels.as_ref().and_then(|l| next_cursor(&l.pageInfo));
+            let closing_next = pull
+                .closingIssuesReferences
+                .as_ref()
+                .and_then(|c| next_cursor(&c.pageInfo));
+            let timeline_next = pull
+                .timelineItems
+                .as_ref()
+                .and_then(|t| next_cursor(&t.pageInfo));
+
+            let mut labels: Vec<String> = pull
+                .labels
+                .and_then(|l| l.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|l| l.name)
+                .collect();
+            let mut closing_issues: Vec<String> = pull
+                .closingIssuesReferences
+                .and_then(|c| c.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|i| i.url)
+                .collect();
+            let mut mentions: Vec<String> = pull
+                .timelineItems
+                .and_then(|t| t.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|event| event.source.and_then(|s| s.url))
+                .collect();
+
+            if let Some(node_id) = pull.id.as_deref() {
+                if let Some(after) = labels_next {
+                    labels.extend(fetch_labels_from(node_id, Some(after)).await?);
+                }
+                if let Some(after) = closing_next {
+                    closing_issues.extend(fetch_closing_issues_from(node_id, Some(after)).await?);
+                }
+                if let Some(after) = timeline_next {
+                    mentions.extend(fetch_cross_references_from(node_id, Some(after)).await?);
+                }
+            }
+            let closing_issues = normalize_issue_urls(closing_issues);
+
+            all_pulls.push(OuterPull {
+                title: pull.title.unwrap_or_default(),
+                url,
+                author: login(pull.author),
+                repository: pull.repository.and_then(|r| r.url).unwrap_or_default(),
+                merged_by: login(pull.mergedBy),
+                merged_at: pull.mergedAt,
+                updated_at: pull.updatedAt.unwrap_or_default(),
+                labels,
+                review_decision: pull.reviewDecision,
+                additions: pull.additions.unwrap_or(0),
+                deletions: pull.deletions.unwrap_or(0),
+                cross_referenced_issues: normalize_issue_urls(
+                    closing_issues.iter().cloned().chain(mentions),
+                ),
+                closing_issues,
+            });
+        }
+
+        if search.pageInfo.hasNextPage {
+            after_cursor = search.pageInfo.endCursor
+        } else {
+            break;
+        }
+    }
+
+    Ok(all_pulls)
+}
+
+pub async fn get_pull_requests(
+    query: &str,
+    label_to_watch: &str,
+) -> anyhow::Result<Vec<OuterPull>> {
+    let pulls = search_pull_requests(query)
+        .await?
+        .into_iter()
+        .filter(|pull| {
+            pull.labels
+                .iter()
+                .any(|label| label.eq_ignore_ascii_case(label_to_watch))
+        })
+        .collect();
+
+    Ok(pulls)
+}
+
+// Same as `search_pull_requests`, but grouped by repository url so callers can batch per
+// project. Pull requests keep their url order within a repository.
+pub async fn get_per_repo_pull_requests(
+    query: &str,
+) -> anyhow::Result<BTreeMap<String, Vec<OuterPull>>> {
+    let mut per_repo: BTreeMap<String, Vec<OuterPull>> = BTreeMap::new();
+    for pull in search_pull_requests(query).await? {
+        per_repo
+            .entry(pull.repository.clone())
+            .or_default()
+            .push(pull);
+    }
+    for pulls in per_repo.values_mut() {
+        pulls.sort_by(|a, b| a.url.cmp(&b.url));
+    }
+
+    Ok(per_repo)
+}
+
+pub async fn search_pull_requests_in_window(
+    base: &SearchQuery,
+    field: DateField,
+    window: DateWindow,
+) -> anyhow::Result<Vec<OuterPull>> {
+    let mut seen = HashSet::new();
+    let mut all_pulls = Vec::new();
+
+    for sub_window in split_window_under_cap(base, field, window).await? {
+        let query = base
+            .clone()
+            .date(field, sub_window.to_search_range())
+            .to_string();
+        for pull in search_pull_requests(&query).await? {
+            if seen.insert(pull.url.clone()) {
+                all_pulls.push(pull);
+            }
+        }
+    }
+
+    Ok(all_pulls)
+}
+
+// The parts of a github.com repository, issue or pull request link. This is the one place
+// that takes such links apart; comment anchors, query strings and trailing slashes are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GitHubUrl<'a> {
+    pub owner: &'a str,
+    pub repo: &'a str,
+    // ("issues", 24) or ("pull", 7) when the link points below the repository.
+    pub item: Option<(&'a str, u64)>,
+}
+
+impl GitHubUrl<'_> {
+    // The repository url, which is what `projects.project_id` holds.
+    pub fn project_id(&self) -> String {
+        format!("https://github.com/{}/{}", self.owner, self.repo)
+    }
+
+    pub fn issue_number(&self) -> Option<u64> {
+        match self.item {
+            Some(("issues", number)) => Some(number),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse_github_url(raw: &str) -> Option<GitHubUrl<'_>> {
+    let trimmed = raw.trim();
+    let without_fragment = trimmed.split(['#', '?']).next()?.trim_end_matches('/');
+    let path = without_fragment
+        .strip_prefix("https://github.com/")
+        .or_else(|| without_fragment.strip_prefix("http://github.com/"))
+        .or_else(|| without_fragment.strip_prefix("github.com/"))?;
+
+    match *path.split('/').collect::<Vec<_>>().as_slice() {
+        [owner, repo] if !owner.is_empty() && !repo.is_empty() => Some(GitHubUrl {
+            owner,
+            repo,
+            item: None,
+        }),
+        [owner, repo, kind @ ("issues" | "pull"), number]
+            if !owner.is_empty() && !repo.is_empty() =>
+        {
+            Some(GitHubUrl {
+                owner,
+                repo,
+                item: Some((kind, number.parse().ok()?)),
+            })
+        }
+        _ => None,
+    }
+}
+
+// Reduces an issue link to `https://github.com/{owner}/{repo}/issues/{number}`, dropping
+// comment anchors and query strings. Pull request and non-GitHub links yield `None`.
+pub fn normalize_issue_url(raw: &str) -> Option<String> {
+    let url = parse_github_url(raw)?;
+    let number = url.issue_number()?;
+    Some(format!("{}/issues/{number}", url.project_id()))
+}
+
+pub fn normalize_issue_urls<I>(raw: I) -> Vec<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut urls = raw
+        .into_iter()
+        .filter_map(|url| normalize_issue_url(&url))
+        .collect::<Vec<_>>();
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+// "https://github.com/owner/repo/issues/24" -> ("owner", "repo", 24)
+pub fn parse_issue_url(url: &str) -> Option<(String, String, u64)> {
+    let url = parse_github_url(url)?;
+    let number = url.issue_number()?;
+    Some((url.owner.to_string(), url.repo.to_string(), number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github_transport::{set_github_transport, FixtureTransport};
+    use crate::rate_limit::RateLimitDeferred;
+
+    fn fixtures() -> FixtureTransport {
+        FixtureTransport::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/github"
+        ))
+    }
+
+    #[tokio::test]
+    async fn search_issues_open_replays_recorded_search() {
+        set_github_transport(fixtures());
+
+        let issues = search_issues_open("label:bounty is:issue repo:owner/repo")
+            .await
+            .unwrap();
+
+        assert_eq!(issues.len(), 1);
+        let issue = &issues[0];
+        assert_eq!(issue.url, "https://github.com/owner/repo/issues/7");
+        assert_eq!(issue.author, "maintainer");
+        assert_eq!(issue.state, "OPEN");
+        assert_eq!(issue.created_at, "2024-02-20T09:00:00Z");
+        assert_eq!(issue.closed_at, None);
+        assert_eq!(issue.repository_stars, 42);
+        assert_eq!(issue.issue_labels, vec!["bounty", "good first issue"]);
+        assert_eq!(issue.assignees, vec!["alice"]);
+        assert_eq!(issue.assignment_events.len(), 1);
+        assert_eq!(
+            issue.assignment_events[0].action,
+            AssignmentAction::Assigned
+        );
+        assert_eq!(issue.assignment_events[0].assignee, "alice");
+
+        // The second comment comes from the follow-up page after cursor "c1".
+        let authors = issue
+            .comments
+            .iter()
+            .map(|c| c.author.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(authors, vec!["alice", "ghost"]);
+        assert_eq!(
+            issue.comments[1].url,
+            "https://github.com/owner/repo/issues/7#issuecomment-2"
+        );
+        assert_eq!(issue.comments[1].updated_at, "2024-02-24T10:30:00Z");
+        assert!(issue.comments_complete);
+    }
+
+    #[tokio::test]
+    async fn get_project_logo_replays_recorded_repository() {
+        set_github_transport(fixtures());
+
+        let logo = get_project_logo("owner", "repo").await.unwrap();
+        assert_eq!(logo, "https://avatars.githubusercontent.com/u/1?v=4");
+    }
+
+    #[tokio::test]
+    async fn recorded_secondary_rate_limit_is_deferred() {
+        set_github_transport(fixtures());
+
+        // The fixture is a 403 asking to retry in an hour, beyond the governor's max wait.
+        let err = get_project_logo("owner", "limited").await.unwrap_err();
+        assert!(err.downcast_ref::<RateLimitDeferred>().is_some(), "{err}");
+    }
+
+    #[test]
+    fn normalize_issue_url_strips_anchors_queries_and_slashes() {
+        let expected = Some("https://github.com/owner/repo/issues/24".to_string());
+        assert_eq!(
+            normalize_issue_url("https://github.com/owner/repo/issues/24"),
+            expected
+        );
+        assert_eq!(
+            normalize_issue_url("https://github.com/owner/repo/issues/24#issuecomment-1"),
+            expected
+        );
+        assert_eq!(
+            normalize_issue_url("https://github.com/owner/repo/issues/24?q=1"),
+            expected
+        );
+        assert_eq!(
+            normalize_issue_url("  https://github.com/owner/repo/issues/24/ "),
+            expected
+        );
+        assert_eq!(
+            normalize_issue_url("http://github.com/owner/repo/issues/24"),
+            expected
+        );
+        assert_eq!(
+            normalize_issue_url("github.com/owner/repo/issues/24"),
+            expected
+        );
+    }
+
+    #[test]
+    fn normalize_issue_url_rejects_non_issue_links() {
+        assert_eq!(
+            normalize_issue_url("https://github.com/owner/repo/pull/24"),
+            None
+        );
+        assert_eq!(normalize_issue_url("https://github.com/owner/repo"), None);
+        assert_eq!(
+            normalize_issue_url("https://github.com/owner/repo/issues/abc"),
+            None
+        );
+        assert_eq!(
+            normalize_issue_url("https://github.com/owner/repo/issues/24/events"),
+            None
+        );
+        assert_eq!(
+            normalize_issue_url("https://gitlab.com/owner/repo/issues/24"),
+            None
+        );
+        assert_eq!(
+            normalize_issue_url("https://github.com//repo/issues/24"),
+            None
+        );
+        assert_eq!(normalize_issue_url(""), None);
+    }
+
+    #[test]
+    fn normalize_issue_urls_sorts_and_dedups() {
+        let urls = normalize_issue_urls(vec![
+            "https://github.com/owner/repo/issues/9#top".to_string(),
+            "https://github.com/owner/repo/pull/3".to_string(),
+            "https://github.com/owner/repo/issues/10".to_string(),
+            "https://github.com/owner/repo/issues/9".to_string(),
+        ]);
+        assert_eq!(
+            urls,
+            vec![
+                "https://github.com/owner/repo/issues/10",
+                "https://github.com/owner/repo/issues/9",
+            ]
+        );
+    }
+}
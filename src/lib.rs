@@ -1,5 +1,23 @@
+pub mod assignments;
+pub mod budget;
+pub mod comments;
+pub mod contributors;
+pub mod crawl_state;
+pub mod date_windows;
 pub mod db_updater;
+pub mod github_transport;
+pub mod graphql;
 pub mod issues_tracker;
+pub mod lifecycle;
+pub mod output_sink;
+pub mod project_metadata;
+pub mod rate_limit;
+pub mod registry;
+pub mod review;
+pub mod schema;
+pub mod search_query;
+pub mod slack;
+pub mod stale_claims;
 use chrono::{Datelike, NaiveDate, Timelike, Utc};
 use dotenv::dotenv;
 use flowsnet_platform_sdk::logger;
@@ -13,56 +31,91 @@ use openai_flows::{
     OpenAIFlows,
 };
 use schedule_flows::{schedule_cron_job, schedule_handler};
-use slack_flows::send_message_to_channel;
 
 use chrono::Duration;
+pub use assignments::*;
+pub use budget::*;
+pub use comments::*;
+pub use contributors::*;
+pub use crawl_state::*;
+pub use date_windows::*;
 pub use db_updater::*;
+pub use github_transport::*;
+pub use graphql::*;
 pub use issues_tracker::*;
+pub use lifecycle::*;
+pub use output_sink::*;
+pub use project_metadata::*;
+pub use rate_limit::*;
+pub use registry::*;
+pub use review::*;
+pub use schema::*;
+pub use search_query::*;
+pub use slack::*;
+pub use stale_claims::*;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPool;
 
 #[no_mangle]
 #[tokio::main(flavor = "current_thread")]
 pub async fn on_deploy() {
+    dotenv().ok();
+    logger::init();
+
+    if let Err(e) = migrate_database().await {
+        log::error!("failed to migrate database: {e}");
+    }
+
     let now = Utc::now();
     let now_minute = now.minute() + 2;
     let cron_time = format!("{:02} {:02} {:02} * *", now_minute, now.hour(), now.day());
     schedule_cron_job(cron_time, String::from("cron_job_evoked")).await;
 }
 
+pub async fn migrate_database() -> anyhow::Result<()> {
+    let pool = PgPool::connect(&std::env::var("DATABASE_URL")?).await?;
+    run_migrations(&pool).await?;
+    log::info!("database schema at version {:?}", expected_schema_version());
+    Ok(())
+}
+
 #[schedule_handler]
 async fn handler(body: Vec<u8>) {
-    let _   = inner(body).await;
+    if let Err(e) = inner(body).await {
+        log::error!("crawl aborted: {e}");
+    }
 }
 
 pub async fn inner(body: Vec<u8>) -> anyhow::Result<()> {
-    let query = "repo:SarthakKeshari/calc_for_everything is:pr is:merged label:hacktoberfest-accepted created:2023-10-01..2023-10-03 review:approved -label:spam -label:invalid";
-
-    let octocrab = get_octo(&GithubLogin::Default);
-
-    // let pulls = octocrab
-    //     .search()
-    //     .issues_and_pull_requests(&query)
-    //     .send()
-    //     .await?;
-    let pulls = octocrab
-        .repos("SarthakKeshari", "calc_for_everything")
-        .list_stargazers()
-        .send()
-        .await?;
-
-    for pull in pulls.items {
-        log::error!("pulls: {:?}", pull);
-        let content = format!("{:?}", pull);
-        let _ = upload_to_gist(&content).await?;
+    let pool = PgPool::connect(&std::env::var("DATABASE_URL")?).await?;
+    ensure_schema_current(&pool).await?;
+
+    let store = CrawlStateStore::Postgres(pool.clone());
+    let mut sink = PostgresSink::new(pool.clone());
+    for project in list_active_projects(&pool, Utc::now()).await? {
+        if let Err(e) = crawl_project(&project, &store, &mut sink).await {
+            log::error!("failed to crawl {}: {e}", project.project_id);
+        }
     }
 
-    let _=   send_message_to_channel("ik8", "general", "text".to_string()).await;
-
-    // let pulls = get_per_repo_pull_requests(&query).await?;
-    // for pull in pulls {
-    //     log::info!("pulls: {:?}", pull.url);
-    // }
+    // After the crawl, so claims closed or linked to a PR since the last run are not flagged.
+    let stale_days = std::env::var("STALE_CLAIM_DAYS")
+        .ok()
+        .and_then(|d| d.parse().ok())
+        .unwrap_or(DEFAULT_STALE_CLAIM_DAYS);
+    let flagged = remind_stale_claims(&pool, stale_days).await?;
+    log::info!("flagged {} stale claims", flagged.len());
+
+    let max_age_hours = std::env::var("PROJECT_METADATA_MAX_AGE_HOURS")
+        .ok()
+        .and_then(|h| h.parse().ok())
+        .unwrap_or(DEFAULT_METADATA_MAX_AGE_HOURS);
+    let refreshed = refresh_project_metadata(&pool, max_age_hours).await?;
+    log::info!("refreshed metadata of {refreshed} projects");
+
+    refresh_contributors(&pool).await?;
+    let posted = post_event_leaderboards(&pool).await?;
+    log::info!("posted {posted} event leaderboards");
 
     Ok(())
 }
@@ -102,43 +155,76 @@ pub async fn inner(body: Vec<u8>) -> anyhow::Result<()> {
     Ok(())
 } */
 
-pub async fn search_issue_init() -> anyhow::Result<()> {
-    let start_date =
-        NaiveDate::parse_from_str("2023-10-01", "%Y-%m-%d").expect("Failed to parse date");
-
-    let mut date_point_vec = Vec::new();
-
-    for i in 0..20 {
-        let three_days_str = (start_date + Duration::days(2 * i as i64))
-            .format("%Y-%m-%d")
-            .to_string();
-
-        date_point_vec.push(three_days_str);
+// How far back the first crawl of a project without `active_from` reaches.
+pub const DEFAULT_CRAWL_LOOKBACK_DAYS: i64 = 30;
+
+// Crawls the tracked-label issues and bounty-label pull requests of one registered project
+// and posts a summary to its Slack channel, if it has one configured.
+pub async fn crawl_project<S: OutputSink>(
+    project: &ProjectConfig,
+    store: &CrawlStateStore,
+    sink: &mut S,
+) -> anyhow::Result<()> {
+    let initial_start = project
+        .active_from
+        .unwrap_or_else(|| Utc::now() - Duration::days(DEFAULT_CRAWL_LOOKBACK_DAYS));
+
+    let mut issue_count = 0;
+    for query in project.issue_queries() {
+        let batch = search_issues_incremental(store, &query, initial_start).await?;
+        issue_count += batch.items.len();
+        report_all(sink, batch.items.iter().map(CrawlRecord::Issue)).await?;
+        batch.commit(store).await?;
     }
 
-    let mut date_range_vec = date_point_vec
-        .windows(2)
-        .map(|x| x.join(".."))
-        .collect::<Vec<_>>();
-
-    let mut texts = String::new();
-    for date_range in date_range_vec {
-        let query =
-            format!("label:hacktoberfest-accepted is:pr is:merged created:{date_range} review:approved -label:spam -label:invalid");
-        let label_to_watch = "hacktoberfest";
-        let pulls = get_pull_requests(&query, label_to_watch).await?;
+    // Pulls go after issues: linking a PR recomputes the lifecycle of the issues it closes,
+    // which only works once those issues are stored.
+    let mut pull_count = 0;
+    for query in project.open_pull_query().into_iter().chain(project.pull_query()) {
+        let batch = search_pull_requests_incremental(store, &query, initial_start).await?;
+        pull_count += batch.items.len();
+        report_all(sink, batch.items.iter().map(CrawlRecord::Pull)).await?;
+        batch.commit(store).await?;
+    }
 
-        for pull in pulls {
-            log::info!("pull: {:?}", pull.url);
-            texts.push_str(&format!("{}\n", pull.url));
-            break;
+    log::info!(
+        "{}: {issue_count} issues, {pull_count} pull requests updated",
+        project.project_id
+    );
+    if let Some((workspace, channel)) = project.slack_target() {
+        if issue_count + pull_count > 0 {
+            let text = format!(
+                "{}: {issue_count} issues and {pull_count} pull requests updated",
+                project.project_id
+            );
+            if let Err(e) = send_slack_message(workspace, channel, &text).await {
+                log::error!("failed to post summary of {}: {e}", project.project_id);
+            }
         }
     }
-
-    let _ = upload_to_gist(&texts).await?;
     Ok(())
 }
 
+pub async fn search_issue_init<S: OutputSink>(
+    plan: &DateWindowPlan,
+    store: &CrawlStateStore,
+    sink: &mut S,
+) -> anyhow::Result<()> {
+    let base = SearchQuery::merged_pull_requests("hacktoberfest-accepted")
+        .created(plan.to_search_range());
+    let label_to_watch = "hacktoberfest-accepted";
+
+    let batch = search_pull_requests_incremental(store, &base, plan.start).await?;
+    let watched = batch
+        .items
+        .iter()
+        .filter(|pull| pull.labels.iter().any(|l| l.eq_ignore_ascii_case(label_to_watch)))
+        .map(CrawlRecord::Pull);
+
+    report_all(sink, watched).await?;
+    batch.commit(store).await
+}
+
 /* pub async fn github_to_db() -> anyhow::Result<()> {
     let start_date =
         NaiveDate::parse_from_str("2023-10-01", "%Y-%m-%d").expect("Failed to parse date");
@@ -0,0 +1,466 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+use sqlx::{Postgres, Transaction};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::issues_tracker::OuterIssue;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueLifecycle {
+    Open,
+    Claimed,
+    InReview,
+    Merged,
+    ClosedUnmerged,
+    Cancelled,
+}
+
+impl IssueLifecycle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IssueLifecycle::Open => "open",
+            IssueLifecycle::Claimed => "claimed",
+            IssueLifecycle::InReview => "in_review",
+            IssueLifecycle::Merged => "merged",
+            IssueLifecycle::ClosedUnmerged => "closed_unmerged",
+            IssueLifecycle::Cancelled => "cancelled",
+        }
+    }
+
+    // `state` and `state_reason` are GitHub's enum values, e.g. "CLOSED" and "NOT_PLANNED".
+    pub fn derive(
+        state: &str,
+        state_reason: Option<&str>,
+        assignee: Option<&str>,
+        linked_pr: Option<&str>,
+        linked_pr_merged: bool,
+    ) -> Self {
+        if linked_pr_merged {
+            return IssueLifecycle::Merged;
+        }
+        if state.eq_ignore_ascii_case("closed") {
+            return match state_reason {
+                Some(reason) if reason.eq_ignore_ascii_case("not_planned") => {
+                    IssueLifecycle::Cancelled
+                }
+                _ => IssueLifecycle::ClosedUnmerged,
+            };
+        }
+        match (assignee, linked_pr) {
+            (_, Some(_)) => IssueLifecycle::InReview,
+            (Some(_), None) => IssueLifecycle::Claimed,
+            (None, None) => IssueLifecycle::Open,
+        }
+    }
+}
+
+impl fmt::Display for IssueLifecycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IssueLifecycle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(IssueLifecycle::Open),
+            "claimed" => Ok(IssueLifecycle::Claimed),
+            "in_review" => Ok(IssueLifecycle::InReview),
+            "merged" => Ok(IssueLifecycle::Merged),
+            "closed_unmerged" => Ok(IssueLifecycle::ClosedUnmerged),
+            "cancelled" => Ok(IssueLifecycle::Cancelled),
+            other => Err(anyhow!("unknown issue status {other:?}")),
+        }
+    }
+}
+
+// When GitHub says `issue` reached `status`, so transitions carry event times instead of
+// crawl times. Falls back to the issue's last update when GitHub keeps no such moment,
+// e.g. for a reopened issue.
+fn reached_at(
+    status: IssueLifecycle,
+    issue: &OuterIssue,
+    first: bool,
+    assigned_at: Option<DateTime<Utc>>,
+    merged_at: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    let parse = |v: &str| {
+        DateTime::parse_from_rfc3339(v)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    };
+    let at = match status {
+        IssueLifecycle::Open if first => parse(&issue.created_at),
+        IssueLifecycle::Claimed => assigned_at,
+        IssueLifecycle::Merged => merged_at,
+        IssueLifecycle::ClosedUnmerged | IssueLifecycle::Cancelled => {
+            issue.closed_at.as_deref().and_then(parse)
+        }
+        _ => None,
+    };
+    at.or_else(|| parse(&issue.updated_at))
+}
+
+// Recomputes `issues.issue_status` from the crawled GitHub state and the stored linked PR,
+// recording a transition row whenever it changes.
+pub async fn update_issue_lifecycle(
+    pool: &PgPool,
+    issue: &OuterIssue,
+) -> anyhow::Result<IssueLifecycle> {
+    let mut tx = pool.begin().await?;
+
+    let rec = sqlx::query!(
+        r#"
+        SELECT i.issue_status, i.issue_linked_pr, i.issue_assigned_at, p.merged_at AS "merged_at?"
+        FROM issues i
+        LEFT JOIN pull_requests p ON p.pull_id = i.issue_linked_pr
+        WHERE i.issue_id = $1
+        FOR UPDATE OF i
+        "#,
+        issue.url
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| anyhow!("issue {} is not tracked", issue.url))?;
+
+    let next = IssueLifecycle::derive(
+        &issue.state,
+        issue.state_reason.as_deref(),
+        issue.assignees.first().map(String::as_str),
+        rec.issue_linked_pr.as_deref(),
+        rec.merged_at.is_some(),
+    );
+
+    if rec.issue_status.as_deref() != Some(next.as_str()) {
+        let changed_at = reached_at(
+            next,
+            issue,
+            rec.issue_status.is_none(),
+            rec.issue_assigned_at,
+            rec.merged_at,
+        );
+        record_transition(
+            &mut tx,
+            &issue.url,
+            rec.issue_status.as_deref(),
+            next,
+            changed_at,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(next)
+}
+
+// Recomputes `issues.issue_status` from the stored row, for changes that arrive without a
+// crawl of the issue itself, e.g. a pull request linking to it. `at` stamps the transition.
+// Placeholder issues that were never crawled are left alone.
+pub async fn refresh_issue_lifecycle(
+    pool: &PgPool,
+    issue_id: &str,
+    at: Option<DateTime<Utc>>,
+) -> anyhow::Result<Option<IssueLifecycle>> {
+    let mut tx = pool.begin().await?;
+
+    let rec = sqlx::query!(
+        r#"
+        SELECT i.issue_state, i.issue_state_reason, i.issue_assignee, i.issue_status,
+               i.issue_linked_pr, p.merged_at AS "merged_at?"
+        FROM issues i
+        LEFT JOIN pull_requests p ON p.pull_id = i.issue_linked_pr
+        WHERE i.issue_id = $1
+        FOR UPDATE OF i
+        "#,
+        issue_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(rec) = rec else {
+        return Ok(None);
+    };
+    let Some(state) = rec.issue_state.as_deref() else {
+        return Ok(None);
+    };
+
+    let next = IssueLifecycle::derive(
+        state,
+        rec.issue_state_reason.as_deref(),
+        rec.issue_assignee.as_deref(),
+        rec.issue_linked_pr.as_deref(),
+        rec.merged_at.is_some(),
+    );
+
+    if rec.issue_status.as_deref() != Some(next.as_str()) {
+        let changed_at = match next {
+            IssueLifecycle::Merged => rec.merged_at.or(at),
+            _ => at,
+        };
+        record_transition(
+            &mut tx,
+            issue_id,
+            rec.issue_status.as_deref(),
+            next,
+            changed_at,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(Some(next))
+}
+
+async fn record_transition(
+    tx: &mut Transaction<'_, Postgres>,
+    issue_id: &str,
+    from: Option<&str>,
+    to: IssueLifecycle,
+    changed_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE issues
+        SET issue_status = $2, updated_at = now()
+        WHERE issue_id = $1
+        "#,
+        issue_id,
+        to.as_str()
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_status_transitions (issue_id, from_status, to_status, changed_at)
+        VALUES ($1, $2, $3, COALESCE($4, now()))
+        "#,
+        issue_id,
+        from,
+        to.as_str(),
+        changed_at
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct IssueLifecycleTimes {
+    pub issue_id: String,
+    pub first_seen_at: DateTime<Utc>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub merged_at: Option<DateTime<Utc>>,
+}
+
+impl IssueLifecycleTimes {
+    pub fn time_to_claim(&self) -> Option<Duration> {
+        self.claimed_at.map(|claimed| claimed - self.first_seen_at)
+    }
+
+    pub fn time_to_merge(&self) -> Option<Duration> {
+        match (self.claimed_at, self.merged_at) {
+            (Some(claimed), Some(merged)) => Some(merged - claimed),
+            _ => None,
+        }
+    }
+}
+
+// First time each milestone was reached for the issues of `project_id`, taken from the
+// transition log. `first_seen_at` is when the issue was opened on GitHub, or its earliest
+// transition for issues stored before the opening time was crawled.
+pub async fn issue_lifecycle_times(
+    pool: &PgPool,
+    project_id: &str,
+) -> anyhow::Result<Vec<IssueLifecycleTimes>> {
+    let times = sqlx::query_as::<_, IssueLifecycleTimes>(
+        r#"
+        SELECT t.issue_id,
+               COALESCE(i.issue_opened_at, MIN(t.changed_at)) AS first_seen_at,
+               MIN(t.changed_at) FILTER (WHERE t.to_status = 'claimed') AS claimed_at,
+               MIN(t.changed_at) FILTER (WHERE t.to_status = 'merged') AS merged_at
+        FROM issue_status_transitions t
+        JOIN issues i ON i.issue_id = t.issue_id
+        WHERE i.project_id = $1
+        GROUP BY t.issue_id, i.issue_opened_at
+        ORDER BY t.issue_id
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(times)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_updater::{
+        record_pull_request_links, upsert_outer_issue, upsert_project, upsert_pull_request,
+    };
+    use crate::issues_tracker::OuterPull;
+
+    const PROJECT: &str = "https://github.com/owner/repo";
+    const ISSUE: &str = "https://github.com/owner/repo/issues/7";
+    const PULL: &str = "https://github.com/owner/repo/pull/8";
+
+    fn issue(state: &str, state_reason: Option<&str>) -> OuterIssue {
+        OuterIssue {
+            title: "Fix the parser".to_string(),
+            url: ISSUE.to_string(),
+            author: "alice".to_string(),
+            body: String::new(),
+            state: state.to_string(),
+            state_reason: state_reason.map(str::to_string),
+            created_at: "2024-03-01T00:00:00Z".to_string(),
+            updated_at: "2024-03-05T00:00:00Z".to_string(),
+            closed_at: (state == "CLOSED").then(|| "2024-03-05T00:00:00Z".to_string()),
+            repository: PROJECT.to_string(),
+            repository_stars: 0,
+            issue_labels: vec!["bounty".to_string()],
+            comments: Vec::new(),
+            comments_complete: true,
+            assignees: Vec::new(),
+            assignment_events: Vec::new(),
+        }
+    }
+
+    fn pull(merged_at: Option<&str>) -> OuterPull {
+        OuterPull {
+            title: "Fix the parser".to_string(),
+            url: PULL.to_string(),
+            author: "bob".to_string(),
+            repository: PROJECT.to_string(),
+            merged_by: String::new(),
+            merged_at: merged_at.map(str::to_string),
+            updated_at: "2024-03-04T00:00:00Z".to_string(),
+            labels: Vec::new(),
+            review_decision: None,
+            additions: 1,
+            deletions: 1,
+            closing_issues: vec![ISSUE.to_string()],
+            cross_referenced_issues: vec![ISSUE.to_string()],
+        }
+    }
+
+    async fn stored_status(pool: &PgPool) -> anyhow::Result<Option<String>> {
+        let rec = sqlx::query!("SELECT issue_status FROM issues WHERE issue_id = $1", ISSUE)
+            .fetch_one(pool)
+            .await?;
+        Ok(rec.issue_status)
+    }
+
+    #[sqlx::test]
+    async fn merged_pull_crawled_after_its_issue_marks_it_merged(
+        pool: PgPool,
+    ) -> anyhow::Result<()> {
+        upsert_project(&pool, PROJECT, "logo").await?;
+        let closed = issue("CLOSED", Some("COMPLETED"));
+        upsert_outer_issue(&pool, &closed).await?;
+        assert_eq!(
+            update_issue_lifecycle(&pool, &closed).await?,
+            IssueLifecycle::ClosedUnmerged
+        );
+
+        let merged = pull(Some("2024-03-05T00:00:00Z"));
+        upsert_pull_request(&pool, &merged).await?;
+        record_pull_request_links(&pool, &merged).await?;
+
+        assert_eq!(stored_status(&pool).await?.as_deref(), Some("merged"));
+        let changed_at = sqlx::query!(
+            "SELECT changed_at FROM issue_status_transitions WHERE issue_id = $1 AND to_status = 'merged'",
+            ISSUE
+        )
+        .fetch_one(&pool)
+        .await?
+        .changed_at;
+        assert_eq!(changed_at.to_rfc3339(), "2024-03-05T00:00:00+00:00");
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn open_pull_moves_its_issue_to_in_review(pool: PgPool) -> anyhow::Result<()> {
+        upsert_project(&pool, PROJECT, "logo").await?;
+        let open = issue("OPEN", None);
+        upsert_outer_issue(&pool, &open).await?;
+        update_issue_lifecycle(&pool, &open).await?;
+
+        let in_review = pull(None);
+        upsert_pull_request(&pool, &in_review).await?;
+        record_pull_request_links(&pool, &in_review).await?;
+
+        assert_eq!(stored_status(&pool).await?.as_deref(), Some("in_review"));
+        Ok(())
+    }
+
+    #[test]
+    fn open_issues_follow_assignee_and_linked_pr() {
+        assert_eq!(
+            IssueLifecycle::derive("OPEN", None, None, None, false),
+            IssueLifecycle::Open
+        );
+        assert_eq!(
+            IssueLifecycle::derive("OPEN", None, Some("alice"), None, false),
+            IssueLifecycle::Claimed
+        );
+        assert_eq!(
+            IssueLifecycle::derive("OPEN", None, Some("alice"), Some("pr"), false),
+            IssueLifecycle::InReview
+        );
+        assert_eq!(
+            IssueLifecycle::derive("OPEN", None, None, Some("pr"), false),
+            IssueLifecycle::InReview
+        );
+    }
+
+    #[test]
+    fn closed_issues_split_on_state_reason() {
+        assert_eq!(
+            IssueLifecycle::derive("CLOSED", Some("COMPLETED"), Some("alice"), None, false),
+            IssueLifecycle::ClosedUnmerged
+        );
+        assert_eq!(
+            IssueLifecycle::derive("CLOSED", None, None, Some("pr"), false),
+            IssueLifecycle::ClosedUnmerged
+        );
+        assert_eq!(
+            IssueLifecycle::derive("closed", Some("not_planned"), None, None, false),
+            IssueLifecycle::Cancelled
+        );
+    }
+
+    #[test]
+    fn merged_linked_pr_wins_over_issue_state() {
+        assert_eq!(
+            IssueLifecycle::derive("OPEN", None, None, Some("pr"), true),
+            IssueLifecycle::Merged
+        );
+        assert_eq!(
+            IssueLifecycle::derive("CLOSED", Some("NOT_PLANNED"), None, Some("pr"), true),
+            IssueLifecycle::Merged
+        );
+    }
+
+    #[test]
+    fn statuses_round_trip_through_strings() {
+        for status in [
+            IssueLifecycle::Open,
+            IssueLifecycle::Claimed,
+            IssueLifecycle::InReview,
+            IssueLifecycle::Merged,
+            IssueLifecycle::ClosedUnmerged,
+            IssueLifecycle::Cancelled,
+        ] {
+            assert_eq!(status.as_str().parse::<IssueLifecycle>().unwrap(), status);
+        }
+        assert!("done".parse::<IssueLifecycle>().is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use github_flows::{get_octo, GithubLogin};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPool;
+use std::io::Write;
+
+use crate::assignments::record_assignments;
+use crate::comments::sync_issue_comments;
+use crate::db_updater::{record_pull_request_links, upsert_outer_issue, upsert_pull_request};
+use crate::issues_tracker::{OuterIssue, OuterPull};
+use crate::lifecycle::update_issue_lifecycle;
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", content = "record", rename_all = "snake_case")]
+pub enum CrawlRecord<'a> {
+    Issue(&'a OuterIssue),
+    Pull(&'a OuterPull),
+}
+
+impl CrawlRecord<'_> {
+    pub fn url(&self) -> &str {
+        match self {
+            CrawlRecord::Issue(issue) => &issue.url,
+            CrawlRecord::Pull(pull) => &pull.url,
+        }
+    }
+}
+
+// Where crawled records end up. The crawler only reports records, callers pick the sink.
+// `finish` is called once after the last record so buffering sinks can flush.
+#[allow(async_fn_in_trait)]
+pub trait OutputSink {
+    async fn report(&mut self, record: CrawlRecord<'_>) -> anyhow::Result<()>;
+
+    async fn finish(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+pub async fn report_all<'a, S: OutputSink>(
+    sink: &mut S,
+    records: impl IntoIterator<Item = CrawlRecord<'a>>,
+) -> anyhow::Result<()> {
+    for record in records {
+        sink.report(record).await?;
+    }
+    sink.finish().await
+}
+
+pub struct NoopSink;
+
+impl OutputSink for NoopSink {
+    async fn report(&mut self, _record: CrawlRecord<'_>) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+// One JSON object per line, appended to `path`. The file is only opened on the first record.
+pub struct JsonlFileSink {
+    path: String,
+    file: Option<std::fs::File>,
+}
+
+impl JsonlFileSink {
+    pub fn new(path: &str) -> Self {
+        JsonlFileSink {
+            path: path.to_string(),
+            file: None,
+        }
+    }
+}
+
+impl OutputSink for JsonlFileSink {
+    async fn report(&mut self, record: CrawlRecord<'_>) -> anyhow::Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            ),
+        };
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> anyhow::Result<()> {
+        if let Some(file) = &mut self.file {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+pub struct PostgresSink {
+    pool: PgPool,
+}
+
+impl PostgresSink {
+    pub fn new(pool: PgPool) -> Self {
+        PostgresSink { pool }
+    }
+}
+
+impl OutputSink for PostgresSink {
+    async fn report(&mut self, record: CrawlRecord<'_>) -> anyhow::Result<()> {
+        match record {
+            CrawlRecord::Issue(issue) => {
+                upsert_outer_issue(&self.pool, issue).await?;
+                record_assignments(&self.pool, issue).await?;
+                update_issue_lifecycle(&self.pool, issue).await?;
+                // The search hit already carries every comment. A failed sync is logged so one
+                // issue does not stop the rest of the batch.
+                if let Err(e) = sync_issue_comments(
+                    &self.pool,
+                    &issue.url,
+                    &issue.comments,
+                    issue.comments_complete,
+                )
+                .await
+                {
+                    log::error!("failed to sync comments on {}: {e}", issue.url);
+                }
+            }
+            CrawlRecord::Pull(pull) => {
+                upsert_pull_request(&self.pool, pull).await?;
+                record_pull_request_links(&self.pool, pull).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Collects record urls and publishes them as a single secret gist on `finish`.
+pub struct GistSink {
+    description: String,
+    lines: Vec<String>,
+}
+
+impl GistSink {
+    pub fn new(description: &str) -> Self {
+        GistSink {
+            description: description.to_string(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+impl OutputSink for GistSink {
+    async fn report(&mut self, record: CrawlRecord<'_>) -> anyhow::Result<()> {
+        self.lines.push(record.url().to_string());
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> anyhow::Result<()> {
+        if self.lines.is_empty() {
+            return Ok(());
+        }
+        let mut content = self.lines.join("\n");
+        content.push('\n');
+        let url = create_gist(&self.description, &content).await?;
+        log::info!("uploaded {} records to {url}", self.lines.len());
+        self.lines.clear();
+        Ok(())
+    }
+}
+
+pub async fn upload_to_gist(content: &str) -> anyhow::Result<String> {
+    create_gist("tracker crawl output", content).await
+}
+
+async fn create_gist(description: &str, content: &str) -> anyhow::Result<String> {
+    let octocrab = get_octo(&GithubLogin::Default);
+    let body = json!({
+        "description": description,
+        "public": false,
+        "files": { "crawl.txt": { "content": content } },
+    });
+    let gist: Value = octocrab.post("gists", Some(&body)).await?;
+
+    Ok(gist["html_url"].as_str().unwrap_or_default().to_string())
+}
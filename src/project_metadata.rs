@@ -0,0 +1,235 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::graphql::parse_graphql;
+use crate::issues_tracker::github_http_post_gql;
+use crate::registry::parse_project_id;
+
+pub const DEFAULT_METADATA_MAX_AGE_HOURS: i32 = 24;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RepoMetadata {
+    pub logo: String,
+    pub description: Option<String>,
+    pub primary_language: Option<String>,
+    pub topics: Vec<String>,
+    pub stars: i32,
+    pub forks: i32,
+    pub license: Option<String>,
+    pub default_branch: Option<String>,
+    pub open_issues: i32,
+    pub has_contributing: bool,
+}
+
+// Everything the project directory shows, in a single query. CONTRIBUTING.md counts in any
+// of the places GitHub itself looks for it.
+pub async fn fetch_repo_metadata(owner: &str, repo: &str) -> anyhow::Result<RepoMetadata> {
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Data {
+        repository: Option<Repository>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Repository {
+        owner: Owner,
+        description: Option<String>,
+        primaryLanguage: Option<Named>,
+        repositoryTopics: Option<Topics>,
+        stargazerCount: Option<i32>,
+        forkCount: Option<i32>,
+        licenseInfo: Option<License>,
+        defaultBranchRef: Option<Named>,
+        issues: Option<Count>,
+        contributingRoot: Option<GitObject>,
+        contributingDocs: Option<GitObject>,
+        contributingGithub: Option<GitObject>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Owner {
+        avatarUrl: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Named {
+        name: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Topics {
+        nodes: Option<Vec<TopicNode>>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct TopicNode {
+        topic: Option<Named>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct License {
+        spdxId: Option<String>,
+        name: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct Count {
+        totalCount: Option<i32>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    struct GitObject {
+        oid: Option<String>,
+    }
+
+    let query_str = r#"
+        query ($owner: String!, $repo: String!) {
+            repository(owner: $owner, name: $repo) {
+                owner {
+                    avatarUrl
+                }
+                description
+                primaryLanguage {
+                    name
+                }
+                repositoryTopics(first: 20) {
+                    nodes {
+                        topic {
+                            name
+                        }
+                    }
+                }
+                stargazerCount
+                forkCount
+                licenseInfo {
+                    spdxId
+                    name
+                }
+                defaultBranchRef {
+                    name
+                }
+                issues(states: OPEN) {
+                    totalCount
+                }
+                contributingRoot: object(expression: "HEAD:CONTRIBUTING.md") {
+                    oid
+                }
+                contributingDocs: object(expression: "HEAD:docs/CONTRIBUTING.md") {
+                    oid
+                }
+                contributingGithub: object(expression: "HEAD:.github/CONTRIBUTING.md") {
+                    oid
+                }
+            }
+            rateLimit {
+                cost
+                remaining
+                resetAt
+            }
+        }
+        "#;
+
+    let variables = serde_json::json!({ "owner": owner, "repo": repo });
+    let response_body = github_http_post_gql(query_str, variables)
+        .await
+        .map_err(|e| anyhow!("Failed to post GraphQL query: {}", e))?;
+
+    let data: Data = parse_graphql(&response_body)?;
+    let repository = data
+        .repository
+        .ok_or_else(|| anyhow!("repository {owner}/{repo} not found"))?;
+
+    Ok(RepoMetadata {
+        logo: repository.owner.avatarUrl.unwrap_or_default(),
+        description: repository.description.filter(|d| !d.is_empty()),
+        primary_language: repository.primaryLanguage.and_then(|l| l.name),
+        topics: repository
+            .repositoryTopics
+            .and_then(|t| t.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|n| n.topic.and_then(|t| t.name))
+            .collect(),
+        stars: repository.stargazerCount.unwrap_or(0),
+        forks: repository.forkCount.unwrap_or(0),
+        // "NOASSERTION" is what GitHub reports for a license file it cannot identify.
+        license: repository
+            .licenseInfo
+            .and_then(|l| l.spdxId.filter(|id| id != "NOASSERTION").or(l.name)),
+        default_branch: repository.defaultBranchRef.and_then(|b| b.name),
+        open_issues: repository.issues.and_then(|i| i.totalCount).unwrap_or(0),
+        has_contributing: repository.contributingRoot.is_some()
+            || repository.contributingDocs.is_some()
+            || repository.contributingGithub.is_some(),
+    })
+}
+
+// Fetches and stores the metadata of an already registered project.
+pub async fn enrich_project(pool: &PgPool, project_id: &str) -> anyhow::Result<RepoMetadata> {
+    let (owner, repo) = parse_project_id(project_id)
+        .ok_or_else(|| anyhow!("not a repository url: {project_id}"))?;
+    let metadata = fetch_repo_metadata(owner, repo).await?;
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE projects
+        SET project_logo = COALESCE(NULLIF($2, ''), project_logo),
+            description = $3,
+            primary_language = $4,
+            topics = $5,
+            stars = $6,
+            forks = $7,
+            license = $8,
+            default_branch = $9,
+            open_issues = $10,
+            has_contributing = $11,
+            enriched_at = now(),
+            updated_at = now()
+        WHERE project_id = $1
+        "#,
+        project_id,
+        metadata.logo,
+        metadata.description,
+        metadata.primary_language,
+        &metadata.topics,
+        metadata.stars,
+        metadata.forks,
+        metadata.license,
+        metadata.default_branch,
+        metadata.open_issues,
+        metadata.has_contributing
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(anyhow!("project {project_id} is not registered"));
+    }
+    Ok(metadata)
+}
+
+// Re-enriches projects never enriched or last enriched more than `max_age_hours` ago.
+// A failing project is logged and skipped, it is retried on the next run.
+pub async fn refresh_project_metadata(pool: &PgPool, max_age_hours: i32) -> anyhow::Result<usize> {
+    let due = sqlx::query_scalar!(
+        r#"
+        SELECT project_id
+        FROM projects
+        WHERE enriched_at IS NULL OR enriched_at < now() - make_interval(hours => $1)
+        ORDER BY enriched_at NULLS FIRST, project_id
+        "#,
+        max_age_hours
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut refreshed = 0;
+    for project_id in due {
+        match enrich_project(pool, &project_id).await {
+            Ok(_) => refreshed += 1,
+            Err(e) => log::error!("failed to enrich {project_id}: {e}"),
+        }
+    }
+    Ok(refreshed)
+}
@@ -0,0 +1,219 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::fmt;
+
+use crate::github_transport::GitHubResponse;
+
+const MAX_RETRIES: u32 = 5;
+
+// Returned instead of sleeping when the budget is spent and the reset is further away
+// than the governor is allowed to wait. Scheduled runs should stop and pick up later.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDeferred {
+    pub reset_at: DateTime<Utc>,
+}
+
+impl fmt::Display for RateLimitDeferred {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GitHub rate limit exhausted until {}", self.reset_at)
+    }
+}
+
+impl std::error::Error for RateLimitDeferred {}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitGovernor {
+    pub remaining: Option<i64>,
+    pub reset_at: Option<DateTime<Utc>>,
+    pub last_cost: Option<i64>,
+    pub low_water: i64,
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimitGovernor {
+    fn default() -> Self {
+        RateLimitGovernor {
+            remaining: None,
+            reset_at: None,
+            last_cost: None,
+            low_water: 50,
+            max_wait: Duration::minutes(5),
+        }
+    }
+}
+
+impl RateLimitGovernor {
+    pub fn observe_headers(&mut self, res: &GitHubResponse) {
+        if let Some(remaining) = res
+            .header("x-ratelimit-remaining")
+            .and_then(|v| v.parse().ok())
+        {
+            self.remaining = Some(remaining);
+        }
+        if let Some(reset) = res
+            .header("x-ratelimit-reset")
+            .and_then(|v| v.parse::<i64>().ok())
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        {
+            self.reset_at = Some(reset);
+        }
+    }
+
+    // Picks up `rateLimit { cost remaining resetAt }` when a GraphQL document asked for it.
+    pub fn observe_graphql(&mut self, body: &[u8]) {
+        #[derive(Deserialize)]
+        struct Envelope {
+            data: Option<Data>,
+        }
+
+        #[derive(Deserialize)]
+        struct Data {
+            rateLimit: Option<RateLimit>,
+        }
+
+        #[derive(Deserialize)]
+        struct RateLimit {
+            cost: Option<i64>,
+            remaining: Option<i64>,
+            resetAt: Option<String>,
+        }
+
+        let Ok(Envelope {
+            data: Some(Data {
+                rateLimit: Some(rate_limit),
+            }),
+        }) = serde_json::from_slice::<Envelope>(body)
+        else {
+            return;
+        };
+
+        self.last_cost = rate_limit.cost.or(self.last_cost);
+        self.remaining = rate_limit.remaining.or(self.remaining);
+        if let Some(reset_at) = rate_limit
+            .resetAt
+            .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+        {
+            self.reset_at = Some(reset_at.with_timezone(&Utc));
+        }
+    }
+
+    // How long to hold off before the next request, or an error when that would exceed `max_wait`.
+    pub fn wait_before_request(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Duration>, RateLimitDeferred> {
+        let (Some(remaining), Some(reset_at)) = (self.remaining, self.reset_at) else {
+            return Ok(None);
+        };
+        let needed = self.last_cost.unwrap_or(1).max(1);
+        if remaining - needed >= self.low_water || reset_at <= now {
+            return Ok(None);
+        }
+
+        let wait = reset_at - now + Duration::seconds(1);
+        if wait > self.max_wait {
+            return Err(RateLimitDeferred { reset_at });
+        }
+        Ok(Some(wait))
+    }
+}
+
+thread_local! {
+    static GOVERNOR: RefCell<RateLimitGovernor> = RefCell::new(RateLimitGovernor::default());
+}
+
+pub fn with_governor<R>(f: impl FnOnce(&mut RateLimitGovernor) -> R) -> R {
+    GOVERNOR.with(|g| f(&mut g.borrow_mut()))
+}
+
+// Sends through `send`, pausing while the governor says the budget is low and retrying
+// 403/429 responses that GitHub marks as secondary rate limits. A Retry-After longer than
+// the governor's `max_wait` is returned as `RateLimitDeferred` instead of slept through.
+pub async fn send_governed<F>(mut send: F) -> anyhow::Result<GitHubResponse>
+where
+    F: FnMut() -> anyhow::Result<GitHubResponse>,
+{
+    let mut attempt = 0;
+    loop {
+        if let Some(wait) = with_governor(|g| g.wait_before_request(Utc::now()))? {
+            log::warn!(
+                "GitHub rate limit budget low, sleeping {}s",
+                wait.num_seconds()
+            );
+            sleep(wait).await;
+        }
+
+        let res = send()?;
+        with_governor(|g| g.observe_headers(&res));
+
+        if !is_rate_limited(&res) {
+            return Ok(res);
+        }
+
+        attempt += 1;
+        if attempt > MAX_RETRIES {
+            log::error!("Giving up after {MAX_RETRIES} rate-limited attempts");
+            return Ok(res);
+        }
+
+        let wait = match res
+            .header("retry-after")
+            .and_then(|v| v.parse::<i64>().ok())
+        {
+            Some(secs) => {
+                let wait = Duration::seconds(secs);
+                if wait > with_governor(|g| g.max_wait) {
+                    return Err(RateLimitDeferred {
+                        reset_at: Utc::now() + wait,
+                    }
+                    .into());
+                }
+                wait
+            }
+            None if res.header("x-ratelimit-remaining") == Some("0") => {
+                match with_governor(|g| g.wait_before_request(Utc::now()))? {
+                    Some(wait) => wait,
+                    None => backoff_with_jitter(attempt),
+                }
+            }
+            None => backoff_with_jitter(attempt),
+        };
+        log::warn!(
+            "GitHub rate limited ({}), retry {attempt}/{MAX_RETRIES} in {}s",
+            res.status,
+            wait.num_seconds()
+        );
+        sleep(wait).await;
+    }
+}
+
+fn is_rate_limited(res: &GitHubResponse) -> bool {
+    match res.status {
+        429 => true,
+        403 => {
+            res.header("retry-after").is_some()
+                || res.header("x-ratelimit-remaining") == Some("0")
+                || String::from_utf8_lossy(&res.body)
+                    .to_lowercase()
+                    .contains("rate limit")
+        }
+        _ => false,
+    }
+}
+
+// Exponential from 2s with up to 50% jitter; the clock is the only entropy source on wasm.
+fn backoff_with_jitter(attempt: u32) -> Duration {
+    let base_ms = 2_000i64 << attempt.saturating_sub(1).min(6);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as i64)
+        .unwrap_or(0);
+    Duration::milliseconds(base_ms + nanos % (base_ms / 2))
+}
+
+async fn sleep(wait: Duration) {
+    if let Ok(wait) = wait.to_std() {
+        tokio::time::sleep(wait).await;
+    }
+}
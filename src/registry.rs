@@ -0,0 +1,194 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::db_updater::{upsert_project, UpsertOutcome};
+use crate::issues_tracker::{get_project_logo, parse_github_url};
+use crate::search_query::SearchQuery;
+
+// Per-project crawl settings kept on the `projects` row. `project_id` is the repository url,
+// e.g. "https://github.com/owner/repo".
+#[derive(Serialize, Deserialize, Clone, Debug, Default, sqlx::FromRow)]
+pub struct ProjectConfig {
+    pub project_id: String,
+    pub tracked_labels: Vec<String>,
+    pub bounty_label: Option<String>,
+    pub maintainers: Vec<String>,
+    pub slack_workspace: Option<String>,
+    pub slack_channel: Option<String>,
+    pub budget_cap: Option<i32>,
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+}
+
+// Loads `ProjectConfig` rows; `clause` holds everything after `FROM projects`.
+pub(crate) fn project_config_query(clause: &str) -> String {
+    format!(
+        r#"
+        SELECT project_id, tracked_labels, bounty_label, maintainers, slack_workspace,
+               slack_channel, budget_cap, active_from, active_until
+        FROM projects
+        {clause}
+        "#
+    )
+}
+
+// "https://github.com/owner/repo" -> ("owner", "repo")
+pub fn parse_project_id(project_id: &str) -> Option<(&str, &str)> {
+    parse_github_url(project_id)
+        .filter(|url| url.item.is_none())
+        .map(|url| (url.owner, url.repo))
+}
+
+impl ProjectConfig {
+    pub fn owner_repo(&self) -> Option<(&str, &str)> {
+        parse_project_id(&self.project_id)
+    }
+
+    // Where crawl summaries and leaderboards go, if both the workspace and channel are set.
+    pub fn slack_target(&self) -> Option<(&str, &str)> {
+        Some((
+            self.slack_workspace.as_deref()?,
+            self.slack_channel.as_deref()?,
+        ))
+    }
+
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        self.active_from.map_or(true, |from| from <= at)
+            && self.active_until.map_or(true, |until| at < until)
+    }
+
+    pub fn is_maintainer(&self, login: &str) -> bool {
+        self.maintainers
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(login))
+    }
+
+    // One query per tracked label, scoped to this repository. Closed issues are included so
+    // the updated-window crawl sees issues being closed or resolved by a merge.
+    pub fn issue_queries(&self) -> Vec<SearchQuery> {
+        let Some((owner, repo)) = self.owner_repo() else {
+            return Vec::new();
+        };
+        self.tracked_labels
+            .iter()
+            .map(|label| SearchQuery::issues(label).repo(&format!("{owner}/{repo}")))
+            .collect()
+    }
+
+    // Merged pull requests carrying the bounty label, if the project pays bounties.
+    pub fn pull_query(&self) -> Option<SearchQuery> {
+        let (owner, repo) = self.owner_repo()?;
+        let label = self.bounty_label.as_deref()?;
+        Some(SearchQuery::merged_pull_requests(label).repo(&format!("{owner}/{repo}")))
+    }
+
+    // Open pull requests in this repository. Crawled so issues move to in_review once a PR
+    // that closes them is opened, before it is labelled or merged.
+    pub fn open_pull_query(&self) -> Option<SearchQuery> {
+        let (owner, repo) = self.owner_repo()?;
+        Some(SearchQuery::open_pull_requests().repo(&format!("{owner}/{repo}")))
+    }
+}
+
+// Creates the project if needed and stores its configuration.
+pub async fn register_project(
+    pool: &PgPool,
+    config: &ProjectConfig,
+) -> anyhow::Result<UpsertOutcome> {
+    let (owner, repo) = config
+        .owner_repo()
+        .ok_or_else(|| anyhow!("not a repository url: {}", config.project_id))?;
+    if let (Some(from), Some(until)) = (config.active_from, config.active_until) {
+        if until <= from {
+            return Err(anyhow!(
+                "active range ends at {until}, before it starts at {from}"
+            ));
+        }
+    }
+    if config.budget_cap.is_some_and(|cap| cap < 0) {
+        return Err(anyhow!("budget cap must not be negative"));
+    }
+
+    let known = sqlx::query!(
+        r#"SELECT EXISTS(SELECT 1 FROM projects WHERE project_id = $1) AS "exists!""#,
+        config.project_id
+    )
+    .fetch_one(pool)
+    .await?
+    .exists;
+
+    if !known {
+        let project_logo = get_project_logo(owner, repo).await?;
+        upsert_project(pool, &config.project_id, &project_logo).await?;
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE projects
+        SET tracked_labels = $2,
+            bounty_label = $3,
+            maintainers = $4,
+            slack_workspace = $5,
+            slack_channel = $6,
+            budget_cap = $7,
+            active_from = $8,
+            active_until = $9,
+            updated_at = now()
+        WHERE project_id = $1
+          AND (tracked_labels, bounty_label, maintainers, slack_workspace, slack_channel, budget_cap, active_from, active_until)
+              IS DISTINCT FROM
+              ($2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        config.project_id,
+        &config.tracked_labels,
+        config.bounty_label,
+        &config.maintainers,
+        config.slack_workspace,
+        config.slack_channel,
+        config.budget_cap,
+        config.active_from,
+        config.active_until
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(match (known, updated) {
+        (false, _) => UpsertOutcome::Inserted,
+        (true, 0) => UpsertOutcome::Unchanged,
+        (true, _) => UpsertOutcome::Updated,
+    })
+}
+
+pub async fn get_project_config(
+    pool: &PgPool,
+    project_id: &str,
+) -> anyhow::Result<Option<ProjectConfig>> {
+    let config = sqlx::query_as::<_, ProjectConfig>(&project_config_query("WHERE project_id = $1"))
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(config)
+}
+
+// Projects whose active range contains `at`. Open-ended bounds count as active.
+pub async fn list_active_projects(
+    pool: &PgPool,
+    at: DateTime<Utc>,
+) -> anyhow::Result<Vec<ProjectConfig>> {
+    let configs = sqlx::query_as::<_, ProjectConfig>(&project_config_query(
+        r#"
+        WHERE (active_from IS NULL OR active_from <= $1)
+          AND (active_until IS NULL OR active_until > $1)
+        ORDER BY project_id
+        "#,
+    ))
+    .bind(at)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(configs)
+}
@@ -0,0 +1,145 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPool;
+
+use crate::db_updater::TrackedIssue;
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "review_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStatus {
+    Queue,
+    Approve,
+    Decline,
+}
+
+impl ReviewStatus {
+    // Untracked issues enter the queue; a decision can only be made from the queue, and
+    // either decision can be sent back to the queue for another look.
+    pub fn can_transition(from: Option<ReviewStatus>, to: ReviewStatus) -> bool {
+        use ReviewStatus::*;
+        matches!(
+            (from, to),
+            (None, Queue)
+                | (Some(Queue), Approve)
+                | (Some(Queue), Decline)
+                | (Some(Approve), Queue)
+                | (Some(Decline), Queue)
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct ReviewTransition {
+    pub transition_id: i64,
+    pub issue_id: String,
+    pub from_status: Option<ReviewStatus>,
+    pub to_status: ReviewStatus,
+    pub actor: String,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn transition_review(
+    pool: &PgPool,
+    issue_id: &str,
+    to: ReviewStatus,
+    actor: &str,
+    reason: Option<&str>,
+) -> anyhow::Result<ReviewTransition> {
+    let mut tx = pool.begin().await?;
+
+    let from: Option<ReviewStatus> = sqlx::query_scalar(
+        r#"
+        SELECT review_status
+        FROM issues
+        WHERE issue_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(issue_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| anyhow!("issue {issue_id} is not tracked"))?;
+
+    if !ReviewStatus::can_transition(from, to) {
+        return Err(anyhow!(
+            "issue {issue_id} cannot move from {:?} to {:?}",
+            from,
+            to
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE issues
+        SET review_status = $2, updated_at = now()
+        WHERE issue_id = $1
+        "#,
+    )
+    .bind(issue_id)
+    .bind(to)
+    .execute(&mut *tx)
+    .await?;
+
+    let transition = sqlx::query_as::<_, ReviewTransition>(
+        r#"
+        INSERT INTO review_transitions (issue_id, from_status, to_status, actor, reason)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(issue_id)
+    .bind(from)
+    .bind(to)
+    .bind(actor)
+    .bind(reason)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(transition)
+}
+
+pub async fn list_issues_by_review_status(
+    pool: &PgPool,
+    status: ReviewStatus,
+) -> anyhow::Result<Vec<TrackedIssue>> {
+    let issues = sqlx::query_as::<_, TrackedIssue>(
+        r#"
+        SELECT *
+        FROM issues
+        WHERE review_status = $1
+        ORDER BY updated_at, issue_id
+        "#,
+    )
+    .bind(status)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(issues)
+}
+
+pub async fn review_queue(pool: &PgPool) -> anyhow::Result<Vec<TrackedIssue>> {
+    list_issues_by_review_status(pool, ReviewStatus::Queue).await
+}
+
+pub async fn review_history(
+    pool: &PgPool,
+    issue_id: &str,
+) -> anyhow::Result<Vec<ReviewTransition>> {
+    let transitions = sqlx::query_as::<_, ReviewTransition>(
+        r#"
+        SELECT *
+        FROM review_transitions
+        WHERE issue_id = $1
+        ORDER BY created_at, transition_id
+        "#,
+    )
+    .bind(issue_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(transitions)
+}
@@ -0,0 +1,63 @@
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPool;
+use std::collections::HashSet;
+use std::fmt;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Clone)]
+pub struct SchemaBehind {
+    pub missing: Vec<i64>,
+}
+
+impl fmt::Display for SchemaBehind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "database schema is behind, missing migrations {:?}; run the deploy hook or run_migrations first",
+            self.missing
+        )
+    }
+}
+
+impl std::error::Error for SchemaBehind {}
+
+pub async fn run_migrations(pool: &PgPool) -> anyhow::Result<()> {
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
+
+pub fn expected_schema_version() -> Option<i64> {
+    MIGRATOR.iter().map(|m| m.version).max()
+}
+
+pub async fn applied_migrations(pool: &PgPool) -> anyhow::Result<HashSet<i64>> {
+    let has_table: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    if !has_table {
+        return Ok(HashSet::new());
+    }
+
+    let versions: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await?;
+
+    Ok(versions.into_iter().collect())
+}
+
+// Fails with `SchemaBehind` unless every migration embedded in this build has been applied.
+pub async fn ensure_schema_current(pool: &PgPool) -> anyhow::Result<()> {
+    let applied = applied_migrations(pool).await?;
+    let missing = MIGRATOR
+        .iter()
+        .map(|m| m.version)
+        .filter(|version| !applied.contains(version))
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        return Err(SchemaBehind { missing }.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,383 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Issue,
+    PullRequest,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemState {
+    Open,
+    Closed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReviewState {
+    None,
+    Required,
+    Approved,
+    ChangesRequested,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DateField {
+    Created,
+    Updated,
+    Closed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchDate {
+    Day(NaiveDate),
+    Instant(DateTime<Utc>),
+}
+
+impl From<NaiveDate> for SearchDate {
+    fn from(day: NaiveDate) -> Self {
+        SearchDate::Day(day)
+    }
+}
+
+impl From<DateTime<Utc>> for SearchDate {
+    fn from(instant: DateTime<Utc>) -> Self {
+        SearchDate::Instant(instant)
+    }
+}
+
+impl fmt::Display for SearchDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchDate::Day(day) => write!(f, "{}", day.format("%Y-%m-%d")),
+            SearchDate::Instant(instant) => write!(f, "{}", instant.format("%Y-%m-%dT%H:%M:%SZ")),
+        }
+    }
+}
+
+// GitHub treats `a..b` as inclusive on both ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateRange {
+    Between(SearchDate, SearchDate),
+    OnOrAfter(SearchDate),
+    Before(SearchDate),
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateRange::Between(from, to) => write!(f, "{from}..{to}"),
+            DateRange::OnOrAfter(from) => write!(f, ">={from}"),
+            DateRange::Before(to) => write!(f, "<{to}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Qualifier {
+    Repo(String),
+    Org(String),
+    Label(String),
+    ExcludeLabel(String),
+    Kind(ItemKind),
+    State(ItemState),
+    Merged,
+    Unmerged,
+    Assignee(String),
+    NoAssignee,
+    Review(ReviewState),
+    Date(DateField, DateRange),
+    Keyword(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    qualifiers: Vec<Qualifier>,
+}
+
+impl SearchQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open_unassigned_issues(label: &str) -> Self {
+        Self::new()
+            .label(label)
+            .kind(ItemKind::Issue)
+            .state(ItemState::Open)
+            .no_assignee()
+            .exclude_label("spam")
+            .exclude_label("invalid")
+    }
+
+    // Issues in any state, for crawls that must also see issues being closed.
+    pub fn issues(label: &str) -> Self {
+        Self::new()
+            .label(label)
+            .kind(ItemKind::Issue)
+            .exclude_label("spam")
+            .exclude_label("invalid")
+    }
+
+    pub fn open_issues(label: &str) -> Self {
+        Self::new()
+            .label(label)
+            .kind(ItemKind::Issue)
+            .state(ItemState::Open)
+            .exclude_label("spam")
+            .exclude_label("invalid")
+    }
+
+    pub fn closed_issues(label: &str) -> Self {
+        Self::new()
+            .label(label)
+            .kind(ItemKind::Issue)
+            .state(ItemState::Closed)
+            .exclude_label("spam")
+            .exclude_label("invalid")
+    }
+
+    pub fn merged_pull_requests(label: &str) -> Self {
+        Self::new()
+            .label(label)
+            .kind(ItemKind::PullRequest)
+            .merged()
+            .review(ReviewState::Approved)
+            .exclude_label("spam")
+            .exclude_label("invalid")
+    }
+
+    // Open pull requests whatever their labels, so the issues they close can be linked while
+    // they are still in review.
+    pub fn open_pull_requests() -> Self {
+        Self::new()
+            .kind(ItemKind::PullRequest)
+            .state(ItemState::Open)
+            .exclude_label("spam")
+            .exclude_label("invalid")
+    }
+
+    pub fn repo(self, owner_repo: &str) -> Self {
+        self.push(Qualifier::Repo(owner_repo.to_string()))
+    }
+
+    pub fn org(self, org: &str) -> Self {
+        self.push(Qualifier::Org(org.to_string()))
+    }
+
+    pub fn label(self, label: &str) -> Self {
+        self.push(Qualifier::Label(label.to_string()))
+    }
+
+    pub fn exclude_label(self, label: &str) -> Self {
+        self.push(Qualifier::ExcludeLabel(label.to_string()))
+    }
+
+    pub fn kind(self, kind: ItemKind) -> Self {
+        self.push(Qualifier::Kind(kind))
+    }
+
+    pub fn state(self, state: ItemState) -> Self {
+        self.push(Qualifier::State(state))
+    }
+
+    pub fn merged(self) -> Self {
+        self.push(Qualifier::Merged)
+    }
+
+    pub fn unmerged(self) -> Self {
+        self.push(Qualifier::Unmerged)
+    }
+
+    pub fn assignee(self, login: &str) -> Self {
+        self.push(Qualifier::Assignee(login.to_string()))
+    }
+
+    pub fn no_assignee(self) -> Self {
+        self.push(Qualifier::NoAssignee)
+    }
+
+    pub fn review(self, review: ReviewState) -> Self {
+        self.push(Qualifier::Review(review))
+    }
+
+    pub fn keyword(self, keyword: &str) -> Self {
+        self.push(Qualifier::Keyword(keyword.to_string()))
+    }
+
+    pub fn created(self, range: DateRange) -> Self {
+        self.date(DateField::Created, range)
+    }
+
+    pub fn updated(self, range: DateRange) -> Self {
+        self.date(DateField::Updated, range)
+    }
+
+    pub fn closed(self, range: DateRange) -> Self {
+        self.date(DateField::Closed, range)
+    }
+
+    // A query only carries one range per date field, so setting it again replaces the old one.
+    pub fn date(mut self, field: DateField, range: DateRange) -> Self {
+        self.qualifiers
+            .retain(|q| !matches!(q, Qualifier::Date(f, _) if *f == field));
+        self.push(Qualifier::Date(field, range))
+    }
+
+    pub fn qualifiers(&self) -> &[Qualifier] {
+        &self.qualifiers
+    }
+
+    fn push(mut self, qualifier: Qualifier) -> Self {
+        if !self.qualifiers.contains(&qualifier) {
+            self.qualifiers.push(qualifier);
+        }
+        self
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rendered = self
+            .qualifiers
+            .iter()
+            .map(render_qualifier)
+            .collect::<Vec<_>>()
+            .join(" ");
+        f.write_str(&rendered)
+    }
+}
+
+fn render_qualifier(qualifier: &Qualifier) -> String {
+    match qualifier {
+        Qualifier::Repo(v) => format!("repo:{}", quote_value(v)),
+        Qualifier::Org(v) => format!("org:{}", quote_value(v)),
+        Qualifier::Label(v) => format!("label:{}", quote_value(v)),
+        Qualifier::ExcludeLabel(v) => format!("-label:{}", quote_value(v)),
+        Qualifier::Kind(ItemKind::Issue) => "is:issue".to_string(),
+        Qualifier::Kind(ItemKind::PullRequest) => "is:pr".to_string(),
+        Qualifier::State(ItemState::Open) => "is:open".to_string(),
+        Qualifier::State(ItemState::Closed) => "is:closed".to_string(),
+        Qualifier::Merged => "is:merged".to_string(),
+        Qualifier::Unmerged => "is:unmerged".to_string(),
+        Qualifier::Assignee(v) => format!("assignee:{}", quote_value(v)),
+        Qualifier::NoAssignee => "no:assignee".to_string(),
+        Qualifier::Review(ReviewState::None) => "review:none".to_string(),
+        Qualifier::Review(ReviewState::Required) => "review:required".to_string(),
+        Qualifier::Review(ReviewState::Approved) => "review:approved".to_string(),
+        Qualifier::Review(ReviewState::ChangesRequested) => "review:changes_requested".to_string(),
+        Qualifier::Date(DateField::Created, range) => format!("created:{range}"),
+        Qualifier::Date(DateField::Updated, range) => format!("updated:{range}"),
+        Qualifier::Date(DateField::Closed, range) => format!("closed:{range}"),
+        Qualifier::Keyword(v) => quote_value(v),
+    }
+}
+
+// GitHub search has no escape for a quote inside a quoted value, so embedded quotes are dropped.
+fn quote_value(value: &str) -> String {
+    let cleaned = value.replace('"', "");
+    if cleaned.is_empty()
+        || cleaned
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, ':' | '(' | ')' | ','))
+    {
+        format!("\"{cleaned}\"")
+    } else {
+        cleaned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn renders_presets_in_qualifier_order() {
+        assert_eq!(
+            SearchQuery::open_unassigned_issues("hacktoberfest").to_string(),
+            "label:hacktoberfest is:issue is:open no:assignee -label:spam -label:invalid"
+        );
+        assert_eq!(
+            SearchQuery::merged_pull_requests("hacktoberfest-accepted")
+                .repo("owner/repo")
+                .to_string(),
+            "label:hacktoberfest-accepted is:pr is:merged review:approved -label:spam -label:invalid repo:owner/repo"
+        );
+        assert_eq!(
+            SearchQuery::open_pull_requests()
+                .repo("owner/repo")
+                .to_string(),
+            "is:pr is:open -label:spam -label:invalid repo:owner/repo"
+        );
+    }
+
+    #[test]
+    fn issues_preset_has_no_state() {
+        let query = SearchQuery::issues("bounty");
+        assert!(!query
+            .qualifiers()
+            .iter()
+            .any(|q| matches!(q, Qualifier::State(_))));
+        assert_eq!(
+            query.to_string(),
+            "label:bounty is:issue -label:spam -label:invalid"
+        );
+    }
+
+    #[test]
+    fn duplicate_qualifiers_are_dropped() {
+        let query = SearchQuery::new()
+            .label("bug")
+            .label("bug")
+            .no_assignee()
+            .no_assignee();
+        assert_eq!(query.to_string(), "label:bug no:assignee");
+    }
+
+    #[test]
+    fn setting_a_date_field_again_replaces_it() {
+        let first = NaiveDate::from_ymd_opt(2023, 10, 1).unwrap();
+        let second = NaiveDate::from_ymd_opt(2023, 11, 1).unwrap();
+        let query = SearchQuery::new()
+            .created(DateRange::OnOrAfter(first.into()))
+            .updated(DateRange::Before(first.into()))
+            .created(DateRange::OnOrAfter(second.into()));
+        assert_eq!(
+            query.to_string(),
+            "updated:<2023-10-01 created:>=2023-11-01"
+        );
+    }
+
+    #[test]
+    fn renders_days_and_instants() {
+        let day = NaiveDate::from_ymd_opt(2023, 10, 1).unwrap();
+        let instant = Utc.with_ymd_and_hms(2023, 10, 2, 12, 30, 5).unwrap();
+        let range = DateRange::Between(day.into(), instant.into());
+        assert_eq!(range.to_string(), "2023-10-01..2023-10-02T12:30:05Z");
+        assert_eq!(
+            SearchQuery::new().closed(range).to_string(),
+            "closed:2023-10-01..2023-10-02T12:30:05Z"
+        );
+    }
+
+    #[test]
+    fn quote_value_quotes_only_when_needed() {
+        assert_eq!(quote_value("bug"), "bug");
+        assert_eq!(quote_value("good first issue"), "\"good first issue\"");
+        assert_eq!(quote_value("area:docs"), "\"area:docs\"");
+        assert_eq!(quote_value("a,b"), "\"a,b\"");
+        assert_eq!(quote_value("(wip)"), "\"(wip)\"");
+        assert_eq!(quote_value(""), "\"\"");
+    }
+
+    #[test]
+    fn quote_value_drops_embedded_quotes() {
+        assert_eq!(quote_value("say \"hi\""), "\"say hi\"");
+        assert_eq!(quote_value("\"\""), "\"\"");
+        assert_eq!(
+            SearchQuery::new().label("help \"wanted\"").to_string(),
+            "label:\"help wanted\""
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::anyhow;
+use http_req::request;
+
+// The endpoint `slack_flows::send_message_to_channel` posts to, overridable the same way.
+// That function drops the response, so callers that must know whether a message went out,
+// e.g. to retry a leaderboard on the next run, go through `send_slack_message` instead.
+const SLACK_API_PREFIX: &str = match option_env!("SLACK_API_PREFIX") {
+    Some(prefix) => prefix,
+    None => "https://slack-flows-extension.vercel.app/api",
+};
+
+const FLOWS_USER_CAPACITY: usize = 100;
+
+extern "C" {
+    // Provided by the flows.network host; slack_flows has no public accessor for it.
+    fn get_flows_user(p: *mut u8) -> i32;
+}
+
+fn flows_user() -> anyhow::Result<String> {
+    let mut user = Vec::<u8>::with_capacity(FLOWS_USER_CAPACITY);
+    let len = unsafe { get_flows_user(user.as_mut_ptr()) };
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= user.capacity())
+        .ok_or_else(|| anyhow!("flows user length {len} out of range"))?;
+    // SAFETY: the host wrote `len` bytes, checked above to fit the allocation.
+    unsafe { user.set_len(len) };
+    Ok(String::from_utf8(user)?)
+}
+
+// Sends `text` to `channel` of the connected `workspace`, failing unless Slack accepted it.
+pub async fn send_slack_message(workspace: &str, channel: &str, text: &str) -> anyhow::Result<()> {
+    let url = format!(
+        "{SLACK_API_PREFIX}/{}/send?team={}&channel={}",
+        urlencoding::encode(&flows_user()?),
+        urlencoding::encode(workspace),
+        urlencoding::encode(channel)
+    );
+    let mut writer = Vec::new();
+    let res = request::post(url, text.as_bytes(), &mut writer)?;
+
+    if !res.status_code().is_success() {
+        return Err(anyhow!(
+            "failed to send to {workspace}/{channel}: {} {}",
+            res.status_code(),
+            String::from_utf8_lossy(&writer)
+        ));
+    }
+    Ok(())
+}
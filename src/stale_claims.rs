@@ -0,0 +1,85 @@
+use github_flows::{get_octo, GithubLogin};
+use sqlx::postgres::PgPool;
+
+use crate::db_updater::TrackedIssue;
+use crate::issues_tracker::parse_issue_url;
+
+pub const DEFAULT_STALE_CLAIM_DAYS: i32 = 7;
+
+// Issues still in the `claimed` lifecycle status, i.e. open, assigned and without a linked PR,
+// that were claimed more than `days` ago with no comment from any assignee in that time. An
+// issue flagged before its current assignment is eligible again.
+pub async fn find_stale_claims(pool: &PgPool, days: i32) -> anyhow::Result<Vec<TrackedIssue>> {
+    let issues = sqlx::query_as::<_, TrackedIssue>(
+        r#"
+        SELECT i.*
+        FROM issues i
+        WHERE i.issue_status = 'claimed'
+          AND i.issue_assigned_at < now() - make_interval(days => $1)
+          AND (i.stale_flagged_at IS NULL OR i.stale_flagged_at < i.issue_assigned_at)
+          AND NOT EXISTS (
+              SELECT 1
+              FROM comments c
+              WHERE c.issue_id = i.issue_id
+                AND (c.creator = i.issue_assignee OR c.creator = ANY(i.issue_assignees))
+                AND c.time > (now() AT TIME ZONE 'UTC') - make_interval(days => $1)
+          )
+        ORDER BY i.issue_assigned_at
+        "#,
+    )
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(issues)
+}
+
+pub async fn flag_stale_claim(pool: &PgPool, issue_id: &str) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE issues
+        SET stale_flagged_at = now(), updated_at = now()
+        WHERE issue_id = $1
+        "#,
+        issue_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn reminder_text(assignee: &str, days: i32) -> String {
+    format!(
+        "Hi @{assignee}, this issue was assigned to you more than {days} days ago and we haven't seen a linked pull request or an update since. \
+Are you still working on it? If we don't hear back, the issue may be unassigned so someone else can pick it up."
+    )
+}
+
+// Posts a reminder on each stale claim and flags it so the next run skips it.
+// Returns the ids of the issues that were flagged.
+pub async fn remind_stale_claims(pool: &PgPool, days: i32) -> anyhow::Result<Vec<String>> {
+    let octocrab = get_octo(&GithubLogin::Default);
+    let mut flagged = Vec::new();
+
+    for issue in find_stale_claims(pool, days).await? {
+        let Some((owner, repo, number)) = parse_issue_url(&issue.issue_id) else {
+            log::warn!("skipping stale claim with unparsable id {}", issue.issue_id);
+            continue;
+        };
+        let assignee = issue.issue_assignee.as_deref().unwrap_or_default();
+
+        if let Err(e) = octocrab
+            .issues(&owner, &repo)
+            .create_comment(number, reminder_text(assignee, days))
+            .await
+        {
+            log::error!("failed to post reminder on {}: {e}", issue.issue_id);
+            continue;
+        }
+
+        flag_stale_claim(pool, &issue.issue_id).await?;
+        flagged.push(issue.issue_id);
+    }
+
+    Ok(flagged)
+}